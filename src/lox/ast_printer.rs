@@ -1,56 +1,247 @@
 use super::expr::*;
+use super::objects::*;
+use super::stmt::*;
+use super::token::{Literal as TokenLiteral, Token};
 
-pub fn print(expr: Expr) -> String {
+// Renders syntax trees as S-expressions, e.g. '1 + 2 * 3' as '(+ 1 (* 2 3))',
+// so the grouping the parser chose is explicit. Parts a node was parsed
+// without, such as a missing 'for' clause, print as '()'.
+pub fn print(expr: &Expr) -> String {
     match expr {
         Expr::Binary(expr) => visit_binary(expr),
+        Expr::Assign(expr) => visit_assign(expr),
         Expr::Grouping(expr) => visit_grouping(expr),
         Expr::Literal(expr) => visit_literal(expr),
-        Expr::Unary(expr) => visit_unary(expr),
+        Expr::Logical(expr) => visit_logical(expr),
         Expr::Ternary(expr) => visit_ternary(expr),
+        Expr::Unary(expr) => visit_unary(expr),
+        Expr::Variable(expr) => expr.name.to_string_lexeme(),
+        Expr::Update(expr) => visit_update(expr),
+        Expr::Call(expr) => visit_call(expr),
+        Expr::List(expr) => visit_list(expr),
+        Expr::Map(expr) => visit_map(expr),
+        Expr::Index(expr) => visit_index(expr),
+        Expr::SetIndex(expr) => visit_set_index(expr),
+        Expr::Get(expr) => visit_get(expr),
+        Expr::Set(expr) => visit_set(expr),
+        Expr::Super(expr) => parenthesize("super", vec![expr.method.to_string_lexeme()]),
+        Expr::This(_) => String::from("this"),
+        Expr::Null => String::from("()"),
     }
 }
 
-fn visit_binary(expr: Binary) -> String {
-    return parenthesize(expr.operator.to_string_lexeme(), vec![expr.left, expr.right]);
+pub fn print_statement(statement: &Statement) -> String {
+    match statement {
+        Statement::Block(statement) => visit_block(statement),
+        Statement::Class(statement) => visit_class(statement),
+        Statement::Expression(statement) => parenthesize(";", vec![print(&statement.expression)]),
+        Statement::For(statement) => visit_for(statement),
+        Statement::ForIn(statement) => visit_for_in(statement),
+        Statement::Function(statement) => visit_function(statement),
+        Statement::If(statement) => visit_if(statement),
+        Statement::Print(statement) => parenthesize("print", vec![print(&statement.expression)]),
+        Statement::Return(statement) => visit_return(statement),
+        Statement::Var(statement) => visit_var(statement),
+        Statement::While(statement) => visit_while(statement),
+        Statement::Break(statement) => visit_jump("break", &statement.label),
+        Statement::Continue(statement) => visit_jump("continue", &statement.label),
+        Statement::Null => String::from("()"),
+    }
 }
 
-fn visit_ternary(expr: Ternary) -> String {
-    return write_ternary(expr.expression, expr.true_part, expr.false_part);
+fn visit_binary(expr: &Binary) -> String {
+    return parenthesize(&expr.operator.to_string_lexeme(), vec![print(&expr.left), print(&expr.right)]);
 }
 
-fn visit_grouping(expr: Grouping) -> String {
-    return parenthesize(String::from("group"), vec![expr.expression]);
+fn visit_assign(expr: &Assign) -> String {
+    return parenthesize("=", vec![expr.name.to_string_lexeme(), print(&expr.value)]);
 }
 
-fn visit_literal(expr: Literal) -> String {
-    if let Object::Nil = expr.value {
-        return String::from("nil");
+fn visit_grouping(expr: &Grouping) -> String {
+    return parenthesize("group", vec![print(&expr.expression)]);
+}
+
+// Strings are quoted, and escaped to keep the output on one line, so that
+// "1" and 1 can be told apart. Floats always show a fraction, so 1.0 and 1
+// can be too.
+fn visit_literal(expr: &Literal) -> String {
+    match &expr.value {
+        Object::String(string) => return format!("{:?}", string),
+        Object::Number(number) => return format!("{:?}", number),
+        value => return value.repr(),
     }
-    return expr.value.to_string();
 }
 
-fn visit_unary(expr: Unary) -> String {
-    return parenthesize(expr.operator.to_string_lexeme(), vec![expr.right]);
+fn visit_logical(expr: &Logical) -> String {
+    return parenthesize(&expr.operator.to_string_lexeme(), vec![print(&expr.left), print(&expr.right)]);
 }
 
-fn parenthesize(name: String, exprs: Vec<Box<Expr>>) -> String {
-    let mut builder: String = String::from("(");
-    builder.push_str(&name);
-    for expr in exprs {
-        builder.push_str(" ");
-        builder.push_str(&print(*expr));
+fn visit_ternary(expr: &Ternary) -> String {
+    let parts = vec![print(&expr.expression), print(&expr.true_part), print(&expr.false_part)];
+    return parenthesize("?:", parts);
+}
+
+fn visit_unary(expr: &Unary) -> String {
+    return parenthesize(&expr.operator.to_string_lexeme(), vec![print(&expr.right)]);
+}
+
+// '++x' prints as '(pre++ x)' and 'x++' as '(post++ x)'; the implicit 1 is left out
+fn visit_update(expr: &Update) -> String {
+    let operator = expr.operator.to_string_lexeme();
+    match operator.as_str() {
+        "++" | "--" => {
+            let name = format!("{}{}", if expr.postfix { "post" } else { "pre" }, operator);
+            return parenthesize(&name, vec![print(&expr.target)]);
+        }
+        _ => return parenthesize(&operator, vec![print(&expr.target), print(&expr.value)]),
+    }
+}
+
+fn visit_call(expr: &Call) -> String {
+    let mut parts = vec![print(&expr.callee)];
+    parts.extend(expr.arguments.iter().map(print));
+    return parenthesize("call", parts);
+}
+
+fn visit_list(expr: &List) -> String {
+    return parenthesize("list", expr.elements.iter().map(print).collect());
+}
+
+fn visit_map(expr: &Map) -> String {
+    let entries = expr
+        .entries
+        .iter()
+        .map(|(key, value)| parenthesize(":", vec![print(key), print(value)]))
+        .collect();
+    return parenthesize("map", entries);
+}
+
+fn visit_index(expr: &Index) -> String {
+    return parenthesize("index", vec![print(&expr.object), print(&expr.index)]);
+}
+
+fn visit_set_index(expr: &SetIndex) -> String {
+    let target = parenthesize("index", vec![print(&expr.object), print(&expr.index)]);
+    return parenthesize("=", vec![target, print(&expr.value)]);
+}
+
+fn visit_get(expr: &Get) -> String {
+    return parenthesize(".", vec![print(&expr.object), expr.name.to_string_lexeme()]);
+}
+
+fn visit_set(expr: &Set) -> String {
+    let target = parenthesize(".", vec![print(&expr.object), expr.name.to_string_lexeme()]);
+    return parenthesize("=", vec![target, print(&expr.value)]);
+}
+
+fn visit_block(statement: &Block) -> String {
+    return parenthesize("block", statement.statements.iter().map(print_statement).collect());
+}
+
+fn visit_class(statement: &Class) -> String {
+    let mut parts = vec![statement.name.to_string_lexeme()];
+    parts.extend(doc(&statement.name));
+    if let Expr::Variable(_) = *statement.superclass {
+        parts.push(parenthesize("<", vec![print(&statement.superclass)]));
+    }
+    parts.extend(statement.methods.iter().map(visit_function));
+    return parenthesize("class", parts);
+}
+
+fn visit_for(statement: &For) -> String {
+    let parts = vec![
+        print_statement(&statement.initializer),
+        print(&statement.condition),
+        print(&statement.increment),
+        print_statement(&statement.body),
+    ];
+    return labeled(&statement.label, parenthesize("for", parts));
+}
+
+fn visit_for_in(statement: &ForIn) -> String {
+    let parts = vec![
+        statement.name.to_string_lexeme(),
+        print(&statement.iterable),
+        print_statement(&statement.body),
+    ];
+    return labeled(&statement.label, parenthesize("for-in", parts));
+}
+
+fn visit_function(statement: &Function) -> String {
+    let params: Vec<String> = statement.params.iter().map(|param| param.to_string_lexeme()).collect();
+    let mut parts = vec![statement.name.to_string_lexeme()];
+    parts.extend(doc(&statement.name));
+    parts.push(format!("({})", params.join(" ")));
+    parts.extend(statement.body.iter().map(print_statement));
+    return parenthesize("fun", parts);
+}
+
+fn visit_if(statement: &If) -> String {
+    let mut parts = vec![print(&statement.condition), print_statement(&statement.then_branch)];
+    if !matches!(*statement.else_branch, Statement::Null) {
+        parts.push(print_statement(&statement.else_branch));
+    }
+    return parenthesize("if", parts);
+}
+
+fn visit_return(statement: &Return) -> String {
+    match *statement.value {
+        Expr::Null => return String::from("(return)"),
+        _ => return parenthesize("return", vec![print(&statement.value)]),
     }
-    builder.push_str(")");
-    return builder;
 }
 
-fn write_ternary(expression: Box<Expr>, true_part: Box<Expr>, false_part: Box<Expr>) -> String {
+fn visit_var(statement: &Var) -> String {
+    let mut parts = vec![statement.name.to_string_lexeme()];
+    parts.extend(doc(&statement.name));
+    parts.push(print(&statement.initializer));
+    return parenthesize("var", parts);
+}
+
+fn visit_while(statement: &While) -> String {
+    let parts = vec![print(&statement.condition), print_statement(&statement.body)];
+    return labeled(&statement.label, parenthesize("while", parts));
+}
+
+fn visit_jump(name: &str, label: &Option<Token>) -> String {
+    match label {
+        Some(label) => return parenthesize(name, vec![label.to_string_lexeme()]),
+        None => return format!("({})", name),
+    }
+}
+
+// The doc comments above a declaration, printed right after its name as
+// '(doc "...")' with one line per comment
+fn doc(name: &Token) -> Option<String> {
+    if name.docs().is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = name
+        .docs()
+        .iter()
+        .map(|doc| match &doc.literal {
+            TokenLiteral::String(text) => text.clone(),
+            _ => String::new(),
+        })
+        .collect();
+    return Some(parenthesize("doc", vec![format!("{:?}", lines.join("\n"))]));
+}
+
+// A labeled loop is wrapped in the label, as in '(label outer (while ...))'
+fn labeled(label: &Option<Token>, loop_statement: String) -> String {
+    match label {
+        Some(label) => return parenthesize("label", vec![label.to_string_lexeme(), loop_statement]),
+        None => return loop_statement,
+    }
+}
+
+fn parenthesize(name: &str, parts: Vec<String>) -> String {
     let mut builder: String = String::from("(");
-    builder.push_str(&print(*expression));
-    builder.push_str(" ? ");
-    builder.push_str(&print(*true_part));
-    builder.push_str(" : ");
-    builder.push_str(&print(*false_part));
-    builder.push_str(")");
+    builder.push_str(name);
+    for part in parts {
+        builder.push(' ');
+        builder.push_str(&part);
+    }
+    builder.push(')');
     return builder;
 }
\ No newline at end of file
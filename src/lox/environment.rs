@@ -1,18 +1,16 @@
 use std::collections::HashMap;
-use std::mem;
 use std::cell::RefCell;
 use std::rc::Rc;
 use super::exception::Exception;
 use super::token::Token;
 use super::objects::*;
-use super::exception;
 
-pub struct Environment<'a> {
-    pub enclosing: Option<Rc<RefCell<Environment<'a>>>>,
+pub struct Environment {
+    pub enclosing: Option<Rc<RefCell<Environment>>>,
     values: HashMap<Vec<u8>, Object>,
 }
 
-impl<'a> Environment<'a> {
+impl Environment {
     pub fn new() -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Environment {
             enclosing: None,
@@ -20,21 +18,25 @@ impl<'a> Environment<'a> {
         }))
     }
 
-    pub fn new_child(enclosing: Rc<RefCell<Environment<'a>>>) -> Rc<RefCell<Self>> {
+    pub fn new_child(enclosing: Rc<RefCell<Environment>>) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Environment {
             enclosing: Some(enclosing),
             values: HashMap::new(),
         }))
     }
 
-    pub fn set_father(&mut self, enclosing: Rc<RefCell<Environment<'a>>>) {
-        self.enclosing = Some(enclosing);
-    }
-
     pub fn define(&mut self, name: Vec<u8>, value: Object) {
         self.values.insert(name, value);
     }
 
+    pub fn values(&self) -> &HashMap<Vec<u8>, Object> {
+        &self.values
+    }
+
+    pub fn get_local(&self, name: &[u8]) -> Option<Object> {
+        self.values.get(name).cloned()
+    }
+
     pub fn get(&self, name: Token) -> Result<Object, Exception> {
         match self.values.get(&name.lexeme) {
             Some(object) => return Ok(object.clone()),
@@ -61,4 +63,27 @@ impl<'a> Environment<'a> {
         }
         Ok(())
     }
+
+    pub fn get_at(&self, distance: usize, name: Token) -> Result<Object, Exception> {
+        if distance == 0 {
+            return self.get(name);
+        }
+        return self.ancestor(distance).borrow().get(name);
+    }
+
+    pub fn assign_at(&mut self, distance: usize, name: Token, value: &Object) -> Result<(), Exception> {
+        if distance == 0 {
+            return self.assign(name, value);
+        }
+        return self.ancestor(distance).borrow_mut().assign(name, value);
+    }
+
+    fn ancestor(&self, distance: usize) -> Rc<RefCell<Environment>> {
+        let mut environment = Rc::clone(self.enclosing.as_ref().expect("Resolved depth exceeds scope chain"));
+        for _ in 1..distance {
+            let enclosing = Rc::clone(environment.borrow().enclosing.as_ref().expect("Resolved depth exceeds scope chain"));
+            environment = enclosing;
+        }
+        return environment;
+    }
 }
\ No newline at end of file
@@ -1,24 +1,35 @@
-use super::token::Token;
+use super::objects::Object;
+use super::token::{Span, Token};
 
 #[derive(Debug,)]
 pub struct Error {
-    pub token: Token,
     pub string: String,
+    pub span: Span,
 }
 
 #[derive(Debug,)]
 pub enum Exception {
-    Error(Error),
-    Continue,
-    Break,
+    Error(Box<Error>),
+    // Number of enclosing loops still to unwind before the jump lands
+    Continue(usize),
+    Break(usize),
+    Return(Object),
+    // Raised by natives, which have no token; the call reports it at its parenthesis
+    Native(String),
     Null,
 }
 
 impl Exception {
     pub fn error(token: Token, string: String) -> Self {
-        Exception::Error(Error {
-            token: token,
-            string: string
-        })
+        let span = token.span;
+        Exception::error_at(span, string)
+    }
+
+    // Like error, but underlines a wider piece of source such as a whole expression
+    pub fn error_at(span: Span, string: String) -> Self {
+        Exception::Error(Box::new(Error {
+            string: string,
+            span: span,
+        }))
     }
 }
\ No newline at end of file
@@ -23,6 +23,7 @@ pub struct Ternary {
 #[derive(Debug, Clone)]
 pub struct Literal {
     pub value: Object,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -41,12 +42,24 @@ pub struct Unary {
 #[derive(Debug, Clone)]
 pub struct Variable {
     pub name: Token,
+    pub depth: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Assign {
     pub name: Token,
     pub value: Box<Expr>,
+    pub depth: Option<usize>,
+}
+
+// Compound assignment ('x += 1') and increment/decrement ('++x', 'x--') on a
+// variable or property. 'value' is the right operand, a literal 1 for '++'/'--'.
+#[derive(Debug, Clone)]
+pub struct Update {
+    pub target: Box<Expr>,
+    pub operator: Token,
+    pub value: Box<Expr>,
+    pub postfix: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +69,60 @@ pub struct Call {
     pub arguments: Vec<Expr>,
 }
 
+#[derive(Debug, Clone)]
+pub struct List {
+    pub elements: Vec<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Map {
+    pub entries: Vec<(Expr, Expr)>,
+    pub span: Span,
+}
+
+// 'bracket' is the closing ']', where out-of-range errors are reported
+#[derive(Debug, Clone)]
+pub struct Index {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SetIndex {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Get {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct Set {
+    pub object: Box<Expr>,
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Super {
+    pub keyword: Token,
+    pub method: Token,
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct This {
+    pub keyword: Token,
+    pub depth: Option<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
     Binary(Binary),
@@ -66,7 +133,16 @@ pub enum Expr {
     Ternary(Ternary),
     Unary(Unary),
     Variable(Variable),
+    Update(Update),
     Call(Call),
+    List(List),
+    Map(Map),
+    Index(Index),
+    SetIndex(SetIndex),
+    Get(Get),
+    Set(Set),
+    Super(Super),
+    This(This),
     Null,
 }
 
@@ -94,9 +170,10 @@ impl Expr {
         })
     }
 
-    pub fn literal(value: Object) -> Self {
+    pub fn literal(value: Object, span: Span) -> Self {
         Expr::Literal(Literal {
-            value: value 
+            value: value,
+            span: span,
         })
     }
 
@@ -117,6 +194,7 @@ impl Expr {
     pub fn variable(name: Token) -> Self {
         Expr::Variable(Variable {
             name: name,
+            depth: None,
         })
     }
 
@@ -124,6 +202,16 @@ impl Expr {
         Expr::Assign(Assign {
             name: name,
             value: Box::new(value),
+            depth: None,
+        })
+    }
+
+    pub fn update(target: Expr, operator: Token, value: Expr, postfix: bool) -> Self {
+        Expr::Update(Update {
+            target: Box::new(target),
+            operator: operator,
+            value: Box::new(value),
+            postfix: postfix,
         })
     }
 
@@ -134,4 +222,90 @@ impl Expr {
             arguments: arguments,
         })
     }
-}
+
+    pub fn list(elements: Vec<Expr>, span: Span) -> Self {
+        Expr::List(List {
+            elements: elements,
+            span: span,
+        })
+    }
+
+    pub fn map(entries: Vec<(Expr, Expr)>, span: Span) -> Self {
+        Expr::Map(Map {
+            entries: entries,
+            span: span,
+        })
+    }
+
+    pub fn index(object: Expr, bracket: Token, index: Expr) -> Self {
+        Expr::Index(Index {
+            object: Box::new(object),
+            bracket: bracket,
+            index: Box::new(index),
+        })
+    }
+
+    pub fn set_index(object: Expr, bracket: Token, index: Expr, value: Expr) -> Self {
+        Expr::SetIndex(SetIndex {
+            object: Box::new(object),
+            bracket: bracket,
+            index: Box::new(index),
+            value: Box::new(value),
+        })
+    }
+
+    pub fn get(object: Expr, name: Token) -> Self {
+        Expr::Get(Get {
+            object: Box::new(object),
+            name: name,
+        })
+    }
+
+    pub fn set(object: Expr, name: Token, value: Expr) -> Self {
+        Expr::Set(Set {
+            object: Box::new(object),
+            name: name,
+            value: Box::new(value),
+        })
+    }
+
+    pub fn super_expr(keyword: Token, method: Token) -> Self {
+        Expr::Super(Super {
+            keyword: keyword,
+            method: method,
+            depth: None,
+        })
+    }
+
+    pub fn this(keyword: Token) -> Self {
+        Expr::This(This {
+            keyword: keyword,
+            depth: None,
+        })
+    }
+
+    // Source covered by the whole expression; synthesized nodes have an empty span
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Binary(expr) => expr.left.span().to(expr.right.span()),
+            Expr::Assign(expr) => expr.name.span.to(expr.value.span()),
+            Expr::Grouping(expr) => expr.expression.span(),
+            Expr::Literal(expr) => expr.span,
+            Expr::Logical(expr) => expr.left.span().to(expr.right.span()),
+            Expr::Ternary(expr) => expr.expression.span().to(expr.false_part.span()),
+            Expr::Unary(expr) => expr.operator.span.to(expr.right.span()),
+            Expr::Variable(expr) => expr.name.span,
+            Expr::Update(expr) => expr.target.span().to(expr.operator.span).to(expr.value.span()),
+            Expr::Call(expr) => expr.callee.span().to(expr.paren.span),
+            Expr::List(expr) => expr.span,
+            Expr::Map(expr) => expr.span,
+            Expr::Index(expr) => expr.object.span().to(expr.bracket.span),
+            Expr::SetIndex(expr) => expr.object.span().to(expr.value.span()),
+            Expr::Get(expr) => expr.object.span().to(expr.name.span),
+            Expr::Set(expr) => expr.object.span().to(expr.value.span()),
+            Expr::Super(expr) => expr.keyword.span.to(expr.method.span),
+            Expr::This(expr) => expr.keyword.span,
+            Expr::Null => Span::default(),
+        }
+    }
+}
\ No newline at end of file
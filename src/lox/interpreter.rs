@@ -1,9 +1,15 @@
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::mem;
 use std::rc::Rc;
 
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
+
 use super::{
-    environment, exception::{self, Exception}, lang::Lox, lox_callable::LoxCallable, objects::{self, *}
+    environment, exception::Exception, lox_callable::{LoxCallable, LoxFunction}, lox_class::LoxClass, lox_instance::LoxInstance, lox_iterator::LoxIterator, lox_list, lox_map::{self, LoxMap}, lox_range::{self, LoxRange}, lox_string, objects::*, prelude
 };
 
 use super::{
@@ -14,24 +20,37 @@ use super::{
     token_type::TokenType,
 };
 
-pub struct Interpreter<'a> {
-    environment: Rc<RefCell<environment::Environment<'a>>>,
+// Integers may grow to this many bits through '<<' and '**'. Past it the
+// operation is refused rather than left to exhaust memory.
+const MAX_INT_BITS: u64 = 1 << 24;
+
+pub struct Interpreter {
+    globals: Rc<RefCell<environment::Environment>>,
+    environment: Rc<RefCell<environment::Environment>>,
 }
 
-impl<'a> Interpreter<'a> {
+impl Interpreter {
     pub fn new() -> Self {
+        let globals = Environment::new();
+        prelude::define_globals(&mut globals.borrow_mut());
         Interpreter {
-            environment: Environment::new(),
+            environment: Rc::clone(&globals),
+            globals: globals,
         }
     }
 
-    pub fn interpret(&mut self, statements: Vec<Statement>) -> Result<Object, Exception> {
+    pub fn globals(&self) -> Rc<RefCell<environment::Environment>> {
+        Rc::clone(&self.globals)
+    }
+
+    // With 'echo' set, as in the REPL, the value of each expression statement
+    // is printed unless it is nil
+    pub fn interpret(&mut self, statements: Vec<Statement>, echo: bool) -> Result<Object, Exception> {
         for statement in statements {
             match self.execute(statement) {
-                Ok(object) => match object {
-                    Object::Nil => (),
-                    _ => println!("{}", self.stringify(object)),
-                },
+                Ok(Object::Nil) => (),
+                Ok(object) if echo => println!("{}", self.stringify(object)),
+                Ok(_) => (),
                 Err(err) => return Err(err),
             }
         }
@@ -47,8 +66,17 @@ impl<'a> Interpreter<'a> {
             Expr::Unary(expr) => self.visit_unary(expr),
             Expr::Ternary(expr) => self.visit_ternary(expr),
             Expr::Variable(expr) => self.visit_variable(expr),
+            Expr::Update(expr) => self.visit_update_expr(expr),
             Expr::Assign(expr) => self.visit_assign_expr(expr),
             Expr::Call(expr) => self.visit_call_expr(expr),
+            Expr::List(expr) => self.visit_list_expr(expr),
+            Expr::Map(expr) => self.visit_map_expr(expr),
+            Expr::Index(expr) => self.visit_index_expr(expr),
+            Expr::SetIndex(expr) => self.visit_set_index_expr(expr),
+            Expr::Get(expr) => self.visit_get_expr(expr),
+            Expr::Set(expr) => self.visit_set_expr(expr),
+            Expr::Super(expr) => self.visit_super_expr(expr),
+            Expr::This(expr) => self.visit_this_expr(expr),
             Expr::Null => return Ok(Object::Nil),
         }
     }
@@ -58,29 +86,88 @@ impl<'a> Interpreter<'a> {
             Statement::Expression(expression) => self.visit_expression_statement(*expression.expression),
             Statement::Print(print) => self.visit_print_statement(*print.expression),
             Statement::Var(var) => self.visit_var_statement(var),
+            Statement::Function(function) => self.visit_function_statement(function),
+            Statement::Return(return_statement) => self.visit_return_statement(return_statement),
             Statement::Block(block) => self.visit_block_statement(block),
+            Statement::Class(class) => self.visit_class_statement(class),
             Statement::If(if_branch) => self.visit_if_statement(if_branch),
             Statement::While(while_branch) => self.visit_while_statement(while_branch),
-            Statement::Break => self.visit_break_statement(),
-            // Statement::Continue => self.visit_continue_statement(),
+            Statement::For(for_loop) => self.visit_for_statement(for_loop),
+            Statement::ForIn(for_in) => self.visit_for_in_statement(for_in),
+            Statement::Break(break_statement) => self.visit_break_statement(break_statement),
+            Statement::Continue(continue_statement) => self.visit_continue_statement(continue_statement),
             // Null is used just for else statements
             Statement::Null => Ok(Object::Nil),
-            _ => Ok(Object::Nil),
         }
     }
 
-    fn execute_block(&mut self, statements: Vec<Statement>, mut old_env: Rc<RefCell<environment::Environment<'a>>>,) -> Result<Object, Exception> {
+    pub fn execute_block(&mut self, statements: Vec<Statement>, environment: Rc<RefCell<environment::Environment>>) -> Result<Object, Exception> {
+        let old_env = mem::replace(&mut self.environment, environment);
+        let mut result = Ok(Object::Nil);
         for statement in statements {
-            self.execute(statement)?;
+            if let Err(err) = self.execute(statement) {
+                result = Err(err);
+                break;
+            }
         }
-        mem::swap(&mut self.environment, &mut old_env);
-        return Ok(Object::Nil);
+        // Restore the enclosing environment even when unwinding (errors, break, return)
+        self.environment = old_env;
+        return result;
     }
 
     fn visit_block_statement(&mut self, block: Block) -> Result<Object, Exception> {
         let new_env = Environment::new_child(Rc::clone(&self.environment));
-        let old_env = mem::replace(&mut self.environment, new_env);
-        self.execute_block(block.statements, old_env)
+        self.execute_block(block.statements, new_env)
+    }
+
+    fn visit_class_statement(&mut self, statement: Class) -> Result<Object, Exception> {
+        let superclass = match *statement.superclass {
+            Expr::Variable(variable) => match self.visit_variable(variable.clone())? {
+                Object::Class(class) => Some(class),
+                _ => return Err(Exception::error(variable.name, String::from("Superclass must be a class."))),
+            },
+            _ => None,
+        };
+
+        let name = statement.name.lexeme.clone();
+        self.environment.borrow_mut().define(name, Object::Nil);
+
+        // Methods of a subclass close over an extra scope that binds 'super'
+        let enclosing = Rc::clone(&self.environment);
+        if let Some(ref superclass) = superclass {
+            self.environment = Environment::new_child(Rc::clone(&enclosing));
+            self.environment
+                .borrow_mut()
+                .define(b"super".to_vec(), Object::Class(Rc::clone(superclass)));
+        }
+
+        let mut methods = HashMap::new();
+        for method in statement.methods {
+            let is_initializer = method.name.lexeme == b"init";
+            let function = LoxFunction::new(Rc::new(method.clone()), Rc::clone(&self.environment), is_initializer);
+            methods.insert(method.name.lexeme, Rc::new(function));
+        }
+
+        let class = LoxClass::new(statement.name.to_string_lexeme(), superclass, methods);
+        self.environment = enclosing;
+        self.environment
+            .borrow_mut()
+            .assign(statement.name, &Object::Class(Rc::new(class)))?;
+        return Ok(Object::Nil);
+    }
+
+    fn visit_function_statement(&mut self, statement: Function) -> Result<Object, Exception> {
+        let name = statement.name.lexeme.clone();
+        let function = LoxFunction::new(Rc::new(statement), Rc::clone(&self.environment), false);
+        self.environment
+            .borrow_mut()
+            .define(name, Object::Callable(Rc::new(function)));
+        return Ok(Object::Nil);
+    }
+
+    fn visit_return_statement(&mut self, statement: Return) -> Result<Object, Exception> {
+        let value = self.evaluate(*statement.value)?;
+        return Err(Exception::Return(value));
     }
 
     fn visit_expression_statement(&mut self, expr: Expr) -> Result<Object, Exception> {
@@ -122,7 +209,7 @@ impl<'a> Interpreter<'a> {
     fn visit_var_statement(&mut self, statement: Var) -> Result<Object, Exception> {
         let mut value = Object::Nil;
         match *statement.initializer {
-            Expr::Literal(Lit { value: Object::Nil }) => (),
+            Expr::Literal(Lit { value: Object::Nil, .. }) => (),
             _ => {
                 value = match self.evaluate(*statement.initializer) {
                     Ok(object) => object,
@@ -144,10 +231,9 @@ impl<'a> Interpreter<'a> {
                     Ok(_) => (),
                     Err(err) => {
                         match err {
-                            Exception::Error(_) => return Err(err),
-                            Exception::Continue => continue,
-                            Exception::Break => {break},
-                            _ => (),
+                            Exception::Continue(0) => continue,
+                            Exception::Break(0) => {break},
+                            _ => return Err(Self::leave_loop(err)),
                         }
                         
                     },
@@ -160,101 +246,463 @@ impl<'a> Interpreter<'a> {
         return Ok(Object::Nil);
     }
 
-    fn visit_break_statement(&mut self) -> Result<Object, Exception> {
-        return Err(Exception::Break)
+    fn visit_for_statement(&mut self, statement: For) -> Result<Object, Exception> {
+        let loop_env = Environment::new_child(Rc::clone(&self.environment));
+        let old_env = mem::replace(&mut self.environment, loop_env);
+        let result = self.run_for_loop(statement);
+        // Restore the enclosing environment even when unwinding
+        self.environment = old_env;
+        return result;
+    }
+
+    fn run_for_loop(&mut self, statement: For) -> Result<Object, Exception> {
+        self.execute(*statement.initializer)?;
+        loop {
+            let condition = self.evaluate(*statement.condition.clone())?;
+            if !self.is_truthy(&condition) {
+                break;
+            }
+            match self.execute(*statement.body.clone()) {
+                Ok(_) => (),
+                // The increment still runs after a 'continue'
+                Err(Exception::Continue(0)) => (),
+                Err(Exception::Break(0)) => break,
+                Err(err) => return Err(Self::leave_loop(err)),
+            }
+            self.evaluate(*statement.increment.clone())?;
+        }
+        return Ok(Object::Nil);
+    }
+
+    fn visit_for_in_statement(&mut self, statement: ForIn) -> Result<Object, Exception> {
+        let iterable = self.evaluate(*statement.iterable)?;
+        let mut iterator = LoxIterator::new(self, iterable, &statement.keyword)?;
+        while let Some(item) = iterator.next(self, &statement.keyword)? {
+            // A fresh environment per iteration, so closures capture that iteration's value
+            let environment = Environment::new_child(Rc::clone(&self.environment));
+            environment.borrow_mut().define(statement.name.lexeme.clone(), item);
+            match self.execute_block(vec![*statement.body.clone()], environment) {
+                Ok(_) | Err(Exception::Continue(0)) => (),
+                Err(Exception::Break(0)) => break,
+                Err(err) => return Err(Self::leave_loop(err)),
+            }
+        }
+        return Ok(Object::Nil);
+    }
+
+    fn visit_break_statement(&mut self, statement: Break) -> Result<Object, Exception> {
+        return Err(Exception::Break(statement.depth))
+    }
+
+    fn visit_continue_statement(&mut self, statement: Continue) -> Result<Object, Exception> {
+        return Err(Exception::Continue(statement.depth))
+    }
+
+    // A labeled jump aimed further out passes through this loop one level closer to its target
+    fn leave_loop(err: Exception) -> Exception {
+        match err {
+            Exception::Break(depth) => Exception::Break(depth - 1),
+            Exception::Continue(depth) => Exception::Continue(depth - 1),
+            _ => err,
+        }
     }
 
     fn visit_assign_expr(&mut self, expr: Assign) -> Result<Object, Exception> {
         let value = self.evaluate(*expr.value)?;
-        match self.environment.borrow_mut().assign(expr.name, &value) {
+        let assigned = match expr.depth {
+            Some(distance) => self.environment.borrow_mut().assign_at(distance, expr.name, &value),
+            None => self.globals.borrow_mut().assign(expr.name, &value),
+        };
+        match assigned {
             Ok(()) => return Ok(value),
             Err(err) => return Err(err),
         }
     }
 
+    fn visit_update_expr(&mut self, expr: Update) -> Result<Object, Exception> {
+        let span = expr.target.span().to(expr.operator.span).to(expr.value.span());
+        let operator = Self::update_operator(&expr.operator);
+        let increments = matches!(expr.operator.token_type, TokenType::PlusPlus | TokenType::MinusMinus);
+        match *expr.target {
+            Expr::Variable(variable) => {
+                let current = self.look_up_variable(variable.name.clone(), variable.depth)?;
+                if increments {
+                    self.check_number_operand(expr.operator, &current)?;
+                }
+                let value = self.evaluate(*expr.value)?;
+                let updated = self.binary_operation(operator, span, current.clone(), value)?;
+                match variable.depth {
+                    Some(distance) => self.environment.borrow_mut().assign_at(distance, variable.name, &updated)?,
+                    None => self.globals.borrow_mut().assign(variable.name, &updated)?,
+                };
+                return Ok(if expr.postfix { current } else { updated });
+            }
+            Expr::Get(get) => {
+                // The object is evaluated once and its property read and written in place
+                let instance = match self.evaluate(*get.object)? {
+                    Object::Instance(instance) => instance,
+                    _ => return Err(Exception::error(get.name, String::from("Only instances have fields."))),
+                };
+                let current = LoxInstance::get(&instance, &get.name)?;
+                if increments {
+                    self.check_number_operand(expr.operator, &current)?;
+                }
+                let value = self.evaluate(*expr.value)?;
+                let updated = self.binary_operation(operator, span, current.clone(), value)?;
+                instance.borrow_mut().set(&get.name, updated.clone());
+                return Ok(if expr.postfix { current } else { updated });
+            }
+            Expr::Index(index) => {
+                let object = self.evaluate(*index.object)?;
+                let key = self.evaluate(*index.index)?;
+                let current = self.subscript_get(&object, &key, &index.bracket)?;
+                if increments {
+                    self.check_number_operand(expr.operator, &current)?;
+                }
+                let value = self.evaluate(*expr.value)?;
+                let updated = self.binary_operation(operator, span, current.clone(), value)?;
+                self.subscript_set(&object, key, updated.clone(), &index.bracket)?;
+                return Ok(if expr.postfix { current } else { updated });
+            }
+            // Unreachable: the parser only accepts variables, properties and subscripts
+            _ => return Err(Exception::Null),
+        }
+    }
+
+    // The plain binary operator an update applies, e.g. '+' for both '+=' and '++'
+    fn update_operator(operator: &Token) -> Token {
+        let token_type = match operator.token_type {
+            TokenType::PlusEqual | TokenType::PlusPlus => TokenType::Plus,
+            TokenType::MinusEqual | TokenType::MinusMinus => TokenType::Minus,
+            TokenType::StarEqual => TokenType::Star,
+            TokenType::SlashEqual => TokenType::Slash,
+            TokenType::PercentEqual => TokenType::Percent,
+            other => other,
+        };
+        return Token::new(token_type, operator.lexeme.clone(), operator.span, TokenLiteral::None);
+    }
+
     fn visit_binary(&mut self, expr: Binary) -> Result<Object, Exception> {
+        let span = expr.left.span().to(expr.right.span());
         let left = self.evaluate(*expr.left)?;
         let right = self.evaluate(*expr.right)?;
-        match expr.operator.token_type {
-            TokenType::Minus => {
-                if let Err(Exception) = self.check_number_binary(expr.operator, &left, &right)
-                {
-                    return Err(Exception);
+        return self.binary_operation(expr.operator, span, left, right);
+    }
+
+    fn binary_operation(&mut self, operator: Token, span: Span, left: Object, right: Object) -> Result<Object, Exception> {
+        match operator.token_type {
+            TokenType::Minus
+            | TokenType::Slash
+            | TokenType::TildeSlash
+            | TokenType::Percent
+            | TokenType::Star
+            | TokenType::StarStar => {
+                self.check_number_binary(span, &left, &right)?;
+                return self.arithmetic(operator, span, left, right);
+            }
+            TokenType::Ampersand
+            | TokenType::Pipe
+            | TokenType::Caret
+            | TokenType::LessLess
+            | TokenType::GreaterGreater => {
+                self.check_integer_binary(span, &left, &right)?;
+                return self.bitwise(operator, span, left.int(), right.int());
+            }
+            TokenType::DotDot | TokenType::DotDotEqual => {
+                if let (Object::Int(start), Object::Int(end)) = (left, right) {
+                    let inclusive = matches!(operator.token_type, TokenType::DotDotEqual);
+                    return Ok(Object::Range(LoxRange::new(start, end, inclusive)));
                 }
-                let left = Object::number(left);
-                let right = Object::number(right);
-                return Ok(Object::Number(left - right));
-            }
-            TokenType::Slash => {
-                self.check_number_binary(expr.operator, &left, &right)?;
-                let left = Object::number(left);
-                let right = Object::number(right);
-                return Ok(Object::Number(left / right));
-            }
-            TokenType::Star => {
-                self.check_number_binary(expr.operator, &left, &right)?;
-                let left = Object::number(left);
-                let right = Object::number(right);
-                return Ok(Object::Number(left * right));
+                return Err(Exception::error_at(span, String::from("Range bounds must be integers.")));
             }
             TokenType::Plus => {
+                if left.is_number() && right.is_number() {
+                    return self.arithmetic(operator, span, left, right);
+                }
                 let values = (left, right);
                 if let (Object::String(mut left_value), Object::String(right_value)) = values {
                     left_value.push_str(&right_value);
                     return Ok(Object::String(left_value));
-                } else if let (Object::Number(left_value), Object::Number(right_value)) = values {
-                    return Ok(Object::Number(left_value + right_value));
-                } else if let (Object::String(left_value)) = values.0 {
+                } else if let Object::String(left_value) = values.0 {
                     let mut string = left_value;
                     string.push_str(&self.stringify(values.1));
                     return Ok(Object::String(string));
                 }
+                let message = String::from("Operands must be two numbers or two strings.");
+                return Err(Exception::error_at(span, message));
             }
             TokenType::Greater => {
-                self.check_number_binary(expr.operator, &left, &right)?;
-                let left = Object::number(left);
-                let right = Object::number(right);
-                return Ok(Object::Boolean(left > right));
+                self.check_number_binary(span, &left, &right)?;
+                let ordering = compare_numbers(&left, &right);
+                return Ok(Object::Boolean(ordering == Some(Ordering::Greater)));
             }
             TokenType::GreaterEqual => {
-                self.check_number_binary(expr.operator, &left, &right)?;
-                let left = Object::number(left);
-                let right = Object::number(right);
-                return Ok(Object::Boolean(left >= right));
+                self.check_number_binary(span, &left, &right)?;
+                let ordering = compare_numbers(&left, &right);
+                return Ok(Object::Boolean(matches!(ordering, Some(Ordering::Greater | Ordering::Equal))));
             }
             TokenType::Less => {
-                self.check_number_binary(expr.operator, &left, &right)?;
-                let left = Object::number(left);
-                let right = Object::number(right);
-                return Ok(Object::Boolean(left < right));
+                self.check_number_binary(span, &left, &right)?;
+                let ordering = compare_numbers(&left, &right);
+                return Ok(Object::Boolean(ordering == Some(Ordering::Less)));
             }
             TokenType::LessEqual => {
-                self.check_number_binary(expr.operator, &left, &right)?;
-                let left = Object::number(left);
-                let right = Object::number(right);
-                return Ok(Object::Boolean(left <= right));
+                self.check_number_binary(span, &left, &right)?;
+                let ordering = compare_numbers(&left, &right);
+                return Ok(Object::Boolean(matches!(ordering, Some(Ordering::Less | Ordering::Equal))));
             }
             TokenType::BangEqual => return Ok(Object::Boolean(!self.is_equal(left, right))),
             TokenType::EqualEqual => return Ok(Object::Boolean(self.is_equal(left, right))),
             _ => return Ok(Object::Nil),
         }
-        // Unreachable
-        return Err(Exception::Null);
+    }
+
+    // Python-like promotion: integers stay exact unless '/' is used or a float
+    // is involved, in which case both operands are widened to floats. As in
+    // Python, dividing by zero is an error whatever the kind of number, and so
+    // is raising zero to a negative power.
+    fn arithmetic(&mut self, operator: Token, span: Span, left: Object, right: Object) -> Result<Object, Exception> {
+        let is_zero = |object: &Object| match object {
+            Object::Int(int) => int.is_zero(),
+            Object::Number(number) => *number == 0.0,
+            _ => false,
+        };
+        let is_negative = match &right {
+            Object::Int(int) => int.is_negative(),
+            Object::Number(number) => *number < 0.0,
+            _ => false,
+        };
+        let divides = matches!(operator.token_type, TokenType::Slash | TokenType::TildeSlash | TokenType::Percent);
+        let zero_to_negative = operator.token_type == TokenType::StarStar && is_zero(&left) && is_negative;
+        if (divides && is_zero(&right)) || zero_to_negative {
+            return Err(Exception::error_at(span, String::from("Division by zero.")));
+        }
+
+        if let (Object::Int(left), Object::Int(right)) = (&left, &right) {
+            match operator.token_type {
+                TokenType::Plus => return Ok(Object::Int(left + right)),
+                TokenType::Minus => return Ok(Object::Int(left - right)),
+                TokenType::Star => return Ok(Object::Int(left * right)),
+                TokenType::TildeSlash => return Ok(Object::Int(left.div_floor(right))),
+                TokenType::Percent => return Ok(Object::Int(left.mod_floor(right))),
+                // A negative exponent gives a fraction, computed as a float below
+                TokenType::StarStar if !right.is_negative() => {
+                    let exponent = if left.bits() <= 1 {
+                        // 0, 1 and -1 stay small: only the parity of the exponent matters
+                        if right.is_zero() { 0 } else if right.is_even() { 2 } else { 1 }
+                    } else {
+                        match right.to_u64() {
+                            Some(exponent) if left.bits().saturating_mul(exponent) <= MAX_INT_BITS => exponent as u32,
+                            _ => return Err(Exception::error_at(span, String::from("Exponent too large."))),
+                        }
+                    };
+                    return Ok(Object::Int(left.pow(exponent)));
+                }
+                _ => (),
+            }
+        }
+
+        let left = Object::number(left);
+        let right = Object::number(right);
+        match operator.token_type {
+            TokenType::Plus => return Ok(Object::Number(left + right)),
+            TokenType::Minus => return Ok(Object::Number(left - right)),
+            TokenType::Star => return Ok(Object::Number(left * right)),
+            TokenType::Slash => return Ok(Object::Number(left / right)),
+            TokenType::StarStar => return Ok(Object::Number(left.powf(right))),
+            TokenType::TildeSlash => return Ok(Object::Number((left / right).floor())),
+            TokenType::Percent => {
+                // The remainder takes the sign of the divisor
+                let remainder = left % right;
+                if remainder != 0.0 && (remainder < 0.0) != (right < 0.0) {
+                    return Ok(Object::Number(remainder + right));
+                }
+                return Ok(Object::Number(remainder));
+            }
+            // Unreachable
+            _ => return Err(Exception::Null),
+        }
+    }
+
+    fn bitwise(&mut self, operator: Token, span: Span, left: BigInt, right: BigInt) -> Result<Object, Exception> {
+        match operator.token_type {
+            TokenType::Ampersand => return Ok(Object::Int(left & right)),
+            TokenType::Pipe => return Ok(Object::Int(left | right)),
+            TokenType::Caret => return Ok(Object::Int(left ^ right)),
+            TokenType::LessLess | TokenType::GreaterGreater => {
+                if right.is_negative() {
+                    return Err(Exception::error_at(span, String::from("Negative shift count.")));
+                }
+                let count = match right.to_u64() {
+                    Some(count) if count <= MAX_INT_BITS => count as usize,
+                    _ => return Err(Exception::error_at(span, String::from("Shift count too large."))),
+                };
+                if let TokenType::LessLess = operator.token_type {
+                    return Ok(Object::Int(left << count));
+                }
+                return Ok(Object::Int(left >> count));
+            }
+            // Unreachable
+            _ => return Err(Exception::Null),
+        }
     }
 
     fn visit_call_expr(&mut self, expr: Call) -> Result<Object, Exception> {
+        let callee_span = expr.callee.span();
         let callee = self.evaluate(*expr.callee)?;
 
-        let arguments: Vec<Object> = vec![];
+        let mut arguments: Vec<Object> = vec![];
         for argument in expr.arguments {
             arguments.push(self.evaluate(argument)?);
         }
 
-        let function: LoxCallable = callee as LoxCallable;
+        let function: Rc<dyn LoxCallable> = match callee {
+            Object::Callable(function) => function,
+            Object::Class(class) => class,
+            _ => {
+                let message = String::from("Can only call functions and classes.");
+                return Err(Exception::error_at(callee_span, message));
+            }
+        };
         if arguments.len() != function.arity() {
-            
+            let message = format!("Expected {} arguments but got {}.", function.arity(), arguments.len());
+            return Err(Exception::error(expr.paren, message));
+        }
+        match function.call(self, arguments) {
+            Err(Exception::Native(message)) => return Err(Exception::error(expr.paren, message)),
+            result => return result,
         }
-        return function.call(self, arguments);
+    }
+
+    fn visit_list_expr(&mut self, expr: List) -> Result<Object, Exception> {
+        let mut elements: Vec<Object> = vec![];
+        for element in expr.elements {
+            elements.push(self.evaluate(element)?);
+        }
+        return Ok(Object::List(Rc::new(RefCell::new(elements))));
+    }
+
+    fn visit_map_expr(&mut self, expr: Map) -> Result<Object, Exception> {
+        let mut map = LoxMap::new();
+        for (key, value) in expr.entries {
+            let key_span = key.span();
+            let key = self.evaluate(key)?;
+            let value = self.evaluate(value)?;
+            if let Err(message) = map.insert(key, value) {
+                return Err(Exception::error_at(key_span, message));
+            }
+        }
+        return Ok(Object::Map(Rc::new(RefCell::new(map))));
+    }
+
+    fn visit_index_expr(&mut self, expr: Index) -> Result<Object, Exception> {
+        let object = self.evaluate(*expr.object)?;
+        let index = self.evaluate(*expr.index)?;
+        return self.subscript_get(&object, &index, &expr.bracket);
+    }
+
+    fn visit_set_index_expr(&mut self, expr: SetIndex) -> Result<Object, Exception> {
+        let object = self.evaluate(*expr.object)?;
+        let index = self.evaluate(*expr.index)?;
+        let value = self.evaluate(*expr.value)?;
+        self.subscript_set(&object, index, value.clone(), &expr.bracket)?;
+        return Ok(value);
+    }
+
+    fn subscript_get(&mut self, object: &Object, index: &Object, bracket: &Token) -> Result<Object, Exception> {
+        let value = match object {
+            Object::List(list) => match index {
+                Object::Range(range) => lox_list::slice_range(&list.borrow(), range)
+                    .map(|elements| Object::List(Rc::new(RefCell::new(elements)))),
+                _ => {
+                    let list = list.borrow();
+                    lox_list::index(list.len(), index).map(|position| list[position].clone())
+                }
+            },
+            // Strings index by character. ASCII text, where each character is one
+            // byte, is indexed directly; otherwise only slices collect the characters.
+            Object::String(string) => match index {
+                Object::Range(range) if string.is_ascii() => lox_list::slice_range(string.as_bytes(), range)
+                    .map(|bytes| Object::String(bytes.into_iter().map(char::from).collect())),
+                Object::Range(range) => {
+                    let characters: Vec<char> = string.chars().collect();
+                    lox_list::slice_range(&characters, range)
+                        .map(|characters| Object::String(characters.into_iter().collect()))
+                }
+                _ if string.is_ascii() => lox_list::index(string.len(), index)
+                    .map(|position| Object::String(string[position..position + 1].to_string())),
+                _ => lox_list::index(string.chars().count(), index)
+                    .map(|position| Object::String(string.chars().nth(position).unwrap().to_string())),
+            },
+            Object::Map(map) => match map.borrow().get(index) {
+                Ok(Some(value)) => Ok(value),
+                Ok(None) => Err(lox_map::undefined_key(index)),
+                Err(message) => Err(message),
+            },
+            _ => Err(String::from("Only lists, maps and strings can be indexed.")),
+        };
+        return value.map_err(|message| Exception::error(bracket.clone(), message));
+    }
+
+    fn subscript_set(&mut self, object: &Object, index: Object, value: Object, bracket: &Token) -> Result<(), Exception> {
+        let stored = match object {
+            Object::List(list) => {
+                let mut list = list.borrow_mut();
+                lox_list::index(list.len(), &index).map(|position| list[position] = value)
+            }
+            Object::Map(map) => map.borrow_mut().insert(index, value),
+            Object::String(_) => Err(String::from("Strings can't be modified.")),
+            _ => Err(String::from("Only lists and maps can be assigned by index.")),
+        };
+        return stored.map_err(|message| Exception::error(bracket.clone(), message));
+    }
+
+    fn visit_get_expr(&mut self, expr: Get) -> Result<Object, Exception> {
+        let object = self.evaluate(*expr.object)?;
+        match object {
+            Object::Instance(instance) => return LoxInstance::get(&instance, &expr.name),
+            Object::List(list) => return lox_list::get(&list, &expr.name),
+            Object::Map(map) => return lox_map::get(&map, &expr.name),
+            Object::Range(range) => return lox_range::get(&range, &expr.name),
+            Object::String(string) => return lox_string::get(&string, &expr.name),
+            _ => (),
+        }
+        return Err(Exception::error(expr.name, String::from("Only instances have properties.")));
+    }
+
+    fn visit_set_expr(&mut self, expr: Set) -> Result<Object, Exception> {
+        let object = self.evaluate(*expr.object)?;
+        let instance = match object {
+            Object::Instance(instance) => instance,
+            _ => return Err(Exception::error(expr.name, String::from("Only instances have fields."))),
+        };
+        let value = self.evaluate(*expr.value)?;
+        instance.borrow_mut().set(&expr.name, value.clone());
+        return Ok(value);
+    }
+
+    fn visit_super_expr(&mut self, expr: Super) -> Result<Object, Exception> {
+        let distance = expr.depth.unwrap_or(0);
+        let superclass = match self.environment.borrow().get_at(distance, expr.keyword.clone())? {
+            Object::Class(class) => class,
+            // Unreachable: 'super' is only ever bound to a class
+            _ => return Err(Exception::Null),
+        };
+        // 'this' is always bound in the scope right inside the one holding 'super'
+        let this = Token::new(TokenType::This, b"this".to_vec(), expr.keyword.span, TokenLiteral::None);
+        let object = self.environment.borrow().get_at(distance - 1, this)?;
+
+        match superclass.find_method(&expr.method.lexeme) {
+            Some(method) => return Ok(Object::Callable(Rc::new(method.bind(object)))),
+            None => {
+                let message = format!("Undefined property '{}'.", expr.method.to_string_lexeme());
+                return Err(Exception::error(expr.method, message));
+            }
+        }
+    }
+
+    fn visit_this_expr(&mut self, expr: This) -> Result<Object, Exception> {
+        return self.look_up_variable(expr.keyword, expr.depth);
     }
 
     fn is_equal(&mut self, a: Object, b: Object) -> bool {
@@ -267,17 +715,13 @@ impl<'a> Interpreter<'a> {
         return a == b;
     }
 
-    fn stringify(&mut self, object: Object) -> String {
+    pub fn stringify(&mut self, object: Object) -> String {
         if let Object::Nil = object {
             return String::from("nil");
         }
 
         if let Object::Number(number) = object {
-            let text: String = number.to_string();
-            if text.ends_with(".0") {
-                return String::from(&text[0..text.len() - 2]);
-            }
-            return text;
+            return format_number(number);
         }
 
         if let Object::String(string) = object {
@@ -292,17 +736,14 @@ impl<'a> Interpreter<'a> {
             Err(err) => return Err(err),
         };
 
-        match expression {
-            Object::Boolean(bool) => {
-                if bool {
-                    let true_part = self.evaluate(*expr.true_part);
-                    return true_part;
-                } else {
-                    let false_part = self.evaluate(*expr.false_part);
-                    return false_part;
-                }
+        if let Object::Boolean(bool) = expression {
+            if bool {
+                let true_part = self.evaluate(*expr.true_part);
+                return true_part;
+            } else {
+                let false_part = self.evaluate(*expr.false_part);
+                return false_part;
             }
-            _ => (),
         }
         // Unreachable
         return Err(Exception::Null);
@@ -331,17 +772,25 @@ impl<'a> Interpreter<'a> {
     }
 
     fn visit_unary(&mut self, expr: Unary) -> Result<Object, Exception> {
-        let mut right = self.evaluate(*expr.right)?;
+        let right = self.evaluate(*expr.right)?;
         match expr.operator.token_type {
             TokenType::Minus => {
                 self.check_number_operand(expr.operator, &right)?;
-                if let Object::Number(number) = right {
-                    return Ok(Object::Number(-number));
+                match right {
+                    Object::Number(number) => return Ok(Object::Number(-number)),
+                    Object::Int(int) => return Ok(Object::Int(-int)),
+                    _ => (),
                 }
             }
             TokenType::Bang => {
                 return Ok(Object::Boolean(!self.is_truthy(&right)));
             }
+            TokenType::Tilde => {
+                if let Object::Int(int) = right {
+                    return Ok(Object::Int(!int));
+                }
+                return Err(Exception::error(expr.operator, String::from("Operand must be an integer.")));
+            }
             _ => (),
         }
         // Unreachable
@@ -349,8 +798,14 @@ impl<'a> Interpreter<'a> {
     }
 
     fn visit_variable(&mut self, expr: Variable) -> Result<Object, Exception> {
-        let t = self.environment.borrow_mut().get(expr.name);
-        return t;
+        return self.look_up_variable(expr.name, expr.depth);
+    }
+
+    fn look_up_variable(&mut self, name: Token, depth: Option<usize>) -> Result<Object, Exception> {
+        match depth {
+            Some(distance) => return self.environment.borrow().get_at(distance, name),
+            None => return self.globals.borrow().get(name),
+        }
     }
 
     fn check_number_operand(
@@ -358,7 +813,7 @@ impl<'a> Interpreter<'a> {
         operator: Token,
         operand: &Object,
     ) -> Result<(), Exception> {
-        if let Object::Number(number) = operand {
+        if operand.is_number() {
             return Ok(());
         }
         return Err(Exception::error(operator, String::from("Operand must be a number.")));
@@ -366,19 +821,29 @@ impl<'a> Interpreter<'a> {
 
     fn check_number_binary(
         &mut self,
-        operator: Token,
+        span: Span,
         left: &Object,
         right: &Object,
     ) -> Result<(), Exception> {
-        if let Object::Number(_number) = left {
-            if let Object::Number(_number) = right {
-                return Ok(());
-            }
+        if left.is_number() && right.is_number() {
+            return Ok(());
+        }
+        return Err(Exception::error_at(span, String::from("Operands must be numbers.")));
+    }
+
+    fn check_integer_binary(
+        &mut self,
+        span: Span,
+        left: &Object,
+        right: &Object,
+    ) -> Result<(), Exception> {
+        if let (Object::Int(_), Object::Int(_)) = (left, right) {
+            return Ok(());
         }
-        return Err(Exception::error(operator, String::from("Operand must be numbers.")));
+        return Err(Exception::error_at(span, String::from("Operands must be integers.")));
     }
 
-    fn is_truthy(&mut self, object: &Object) -> bool {
+    pub fn is_truthy(&mut self, object: &Object) -> bool {
         match object {
             Object::Boolean(bool) => return *bool,
             Object::Nil => return false,
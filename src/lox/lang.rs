@@ -1,68 +1,291 @@
-use std::cell::RefCell;
+use std::env;
 use std::error::Error;
+use std::fs;
 use std::fs::File;
-use std::io;
 use std::io::Read;
+use std::mem;
+use std::path::PathBuf;
 use std::process;
 
-use crate::lox::expr;
-use crate::lox::interpreter;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
 
 use super::exception::Exception;
 use super::interpreter::Interpreter;
 use super::token_type::TokenType;
 
-// use super::ast_printer;
+use super::ast_printer;
 use super::scanner::*;
 use super::token::*;
 use super::parser::*;
+use super::resolver::*;
 
+// Exit codes follow the BSD sysexits.h conventions
+pub const EX_USAGE: i32 = 64;
+pub const EX_DATAERR: i32 = 65;
+pub const EX_NOINPUT: i32 = 66;
+pub const EX_SOFTWARE: i32 = 70;
 
 pub struct Lox {
     had_error: bool,
     had_runtime_error: bool,
+    // Kept alive between runs so globals survive across REPL lines and chunks
+    interpreter: Interpreter,
+    // Every text run so far, indexed by Span::source, to quote it in diagnostics
+    sources: Vec<Source>,
+    // Name given to the next text run: a script path, or '<repl>'
+    file_name: String,
+}
+
+struct Source {
+    name: String,
+    text: Vec<u8>,
 }
 
 impl Lox {
     pub fn new() -> Self {
-        Lox { had_error: false, had_runtime_error: false }
+        Lox {
+            had_error: false,
+            had_runtime_error: false,
+            interpreter: Interpreter::new(),
+            sources: vec![],
+            file_name: String::from("<repl>"),
+        }
+    }
+
+    // Drops every global defined so far and starts over with a fresh prelude
+    pub fn reset(&mut self) {
+        self.interpreter = Interpreter::new();
+        self.had_error = false;
+        self.had_runtime_error = false;
     }
 
     pub fn run_file(&mut self, path: &String) -> Result<(), Box<dyn Error>> {
         let mut file = File::open(path)?;
         let mut buffer = vec![];
         file.read_to_end(&mut buffer)?;
-        self.run(buffer);
+        self.file_name = path.clone();
+        self.run(buffer, false);
         if self.had_error {
-            process::exit(1);
+            process::exit(EX_DATAERR);
         }
         if self.had_runtime_error {
-            process::exit(1);
+            process::exit(EX_SOFTWARE);
+        }
+        Ok(())
+    }
+
+    // Parses a script and prints its syntax tree, one statement per line, without
+    // running it. Doc comments are kept and shown on their declarations.
+    pub fn print_ast_file(&mut self, path: &String) -> Result<(), Box<dyn Error>> {
+        let mut file = File::open(path)?;
+        let mut buffer = vec![];
+        file.read_to_end(&mut buffer)?;
+        self.file_name = path.clone();
+        let source_id = self.add_source(buffer.clone());
+        let mut scanner: Scanner = Scanner::new(buffer, source_id);
+        scanner.keep_doc_comments();
+        let tokens: &Vec<Token> = match scanner.scan_tokens() {
+            Ok(tokens) => tokens,
+            Err(errors) => {
+                for error in errors {
+                    self.error(error.0, error.1);
+                }
+                process::exit(EX_DATAERR);
+            }
+        };
+        let mut parser: Parser = Parser::new(tokens);
+        match parser.parse() {
+            Ok(statements) => {
+                for statement in statements {
+                    println!("{}", ast_printer::print_statement(&statement));
+                }
+            }
+            Err(errors) => {
+                for error in errors {
+                    self.token_error(error.0, error.1);
+                }
+                process::exit(EX_DATAERR);
+            }
         }
         Ok(())
     }
 
     pub fn run_prompt(&mut self) {
-        println!("Starting");
+        let mut editor = match DefaultEditor::new() {
+            Ok(editor) => editor,
+            Err(err) => {
+                eprintln!("Could not start the line editor: {}", err);
+                return;
+            }
+        };
+        let history = history_path();
+        if let Some(ref path) = history {
+            // A missing history file just means this is the first session
+            let _ = editor.load_history(path);
+        }
+
+        println!("Starting (type :help for commands)");
+        let mut buffer = String::new();
         loop {
-            print!("> ");
-            let mut input = String::new();
-            io::stdin()
-                .read_line(&mut input)
-                .expect("Failed to read line");
-            if input.is_empty() {
-                break;
+            let prompt = if buffer.is_empty() { "> " } else { "... " };
+            let line = match editor.readline(prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => {
+                    buffer.clear();
+                    continue;
+                }
+                Err(ReadlineError::Eof) => break,
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    break;
+                }
             };
-            let code = input.into_bytes();
-            self.run(code);
+
+            if buffer.is_empty() && line.trim_start().starts_with(':') {
+                let _ = editor.add_history_entry(line.as_str());
+                if !self.meta_command(line.trim()) {
+                    break;
+                }
+                continue;
+            }
+
+            buffer.push_str(&line);
+            buffer.push('\n');
+            // A blank line forces evaluation of whatever was typed so far
+            if !line.trim().is_empty() && self.is_incomplete(&buffer) {
+                continue;
+            }
+
+            let code = mem::take(&mut buffer);
+            if code.trim().is_empty() {
+                continue;
+            }
+            let _ = editor.add_history_entry(code.trim_end());
+            self.run(code.into_bytes(), true);
+            self.had_error = false;
+        }
+
+        if let Some(ref path) = history {
+            let _ = editor.save_history(path);
+        }
+    }
+
+    // Input ran out mid-construct: the user is still typing a block, call, string or comment
+    fn is_incomplete(&self, source: &str) -> bool {
+        let mut scanner: Scanner = Scanner::new(source.as_bytes().to_vec(), 0);
+        let tokens: &Vec<Token> = match scanner.scan_tokens() {
+            Ok(tokens) => tokens,
+            Err(_) => return scanner.is_unterminated(),
+        };
+        let mut parser: Parser = Parser::new(tokens);
+        match parser.parse() {
+            Ok(_) => return false,
+            Err(errors) => return errors.iter().any(|error| error.0.token_type == TokenType::Eof),
         }
     }
 
-    pub fn run(&mut self, source: Vec<u8>) {
-        let mut scanner: Scanner = Scanner::new(source);
-        let tokens: &Vec<Token> = scanner.scan_tokens();
+    // Returns false when the session should end
+    fn meta_command(&mut self, command: &str) -> bool {
+        let (name, argument) = match command.split_once(' ') {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (command, ""),
+        };
+        match name {
+            ":env" => self.print_globals(),
+            ":ast" => self.print_ast(argument),
+            ":tokens" => self.print_tokens(argument),
+            ":load" => match fs::read(argument) {
+                Ok(source) => {
+                    let repl_name = mem::replace(&mut self.file_name, String::from(argument));
+                    self.run(source, false);
+                    self.file_name = repl_name;
+                    self.had_error = false;
+                }
+                Err(err) => eprintln!("Could not load '{}': {}", argument, err),
+            },
+            ":reset" => {
+                self.reset();
+                println!("Interpreter state cleared.");
+            }
+            ":help" => {
+                println!(":env            list global variables");
+                println!(":ast <expr>     show the syntax tree of an expression");
+                println!(":tokens <src>   show the tokens scanned from source");
+                println!(":load <file>    run a file in this session");
+                println!(":reset          forget every definition");
+                println!(":quit           leave the REPL");
+            }
+            ":quit" | ":q" => return false,
+            _ => eprintln!("Unknown command '{}'. Type :help for a list of commands.", name),
+        }
+        return true;
+    }
+
+    fn print_globals(&mut self) {
+        let globals = self.interpreter.globals();
+        let globals = globals.borrow();
+        let mut names: Vec<&Vec<u8>> = globals.values().keys().collect();
+        names.sort();
+        for name in names {
+            let value = globals.values()[name].clone();
+            println!("{} = {}", String::from_utf8_lossy(name), self.interpreter.stringify(value));
+        }
+    }
+
+    fn print_ast(&mut self, source: &str) {
+        let source_id = self.add_source(source.as_bytes().to_vec());
+        let mut scanner: Scanner = Scanner::new(source.as_bytes().to_vec(), source_id);
+        let tokens: &Vec<Token> = match scanner.scan_tokens() {
+            Ok(tokens) => tokens,
+            Err(errors) => {
+                for error in errors {
+                    self.error(error.0, error.1);
+                }
+                self.had_error = false;
+                return;
+            }
+        };
         let mut parser: Parser = Parser::new(tokens);
-        let expression = match parser.parse() {
+        match parser.parse_expression() {
+            Ok(expr) => println!("{}", ast_printer::print(&expr)),
+            Err(error) => self.token_error(error.0, error.1),
+        }
+        self.had_error = false;
+    }
+
+    fn print_tokens(&mut self, source: &str) {
+        let source_id = self.add_source(source.as_bytes().to_vec());
+        let mut scanner: Scanner = Scanner::new(source.as_bytes().to_vec(), source_id);
+        match scanner.scan_tokens() {
+            Ok(tokens) => {
+                for token in tokens {
+                    println!("{}", token);
+                }
+            }
+            Err(errors) => {
+                for error in errors {
+                    self.error(error.0, error.1);
+                }
+                self.had_error = false;
+            }
+        }
+    }
+
+    pub fn run(&mut self, source: Vec<u8>, echo: bool) {
+        let source_id = self.add_source(source.clone());
+        let mut scanner: Scanner = Scanner::new(source, source_id);
+        let tokens: &Vec<Token> = match scanner.scan_tokens() {
+            Ok(tokens) => tokens,
+            Err(errors) => {
+                for error in errors {
+                    self.error(error.0, error.1);
+                }
+                return;
+            }
+        };
+        let mut parser: Parser = Parser::new(tokens);
+        let mut expression = match parser.parse() {
             Ok(expr) => expr,
             Err(errors) => {
                 for error in errors {
@@ -71,35 +294,89 @@ impl Lox {
                 return;
             },
         };
-        let mut interpreter: Interpreter = Interpreter::new();
-        match interpreter.interpret(expression) {
+        let mut resolver: Resolver = Resolver::new();
+        if let Err(errors) = resolver.resolve(&mut expression) {
+            for error in errors {
+                self.token_error(error.0, error.1);
+            }
+            return;
+        }
+        match self.interpreter.interpret(expression, echo) {
             Ok(_) => (),
             Err(err) => self.run_time_error(err),
         }
     }
 
-    pub fn error(&mut self, line: u32, message: String) {
-        self.report(line, String::new(), message);
+    // Sources are kept for the whole session: code from any of them may still run
+    fn add_source(&mut self, text: Vec<u8>) -> u32 {
+        self.sources.push(Source {
+            name: self.file_name.clone(),
+            text: text,
+        });
+        return (self.sources.len() - 1) as u32;
+    }
+
+    pub fn error(&mut self, span: Span, message: String) {
+        self.report(span, message);
     }
 
     pub fn run_time_error(&mut self, err: Exception) {
         if let Exception::Error(err) = err {
-            println!("{} \n[line {}]", err.string, err.token.line);
+            eprintln!("{}", self.render("runtime error", err.span, &err.string));
             self.had_runtime_error = true;
         }
     }
 
-    pub fn report(&mut self, line: u32, col: String, message: String) {
-        println!("[line {0}] Error {1}: {2}", line, col, message);
+    pub fn report(&mut self, span: Span, message: String) {
+        eprintln!("{}", self.render("error", span, &message));
         self.had_error = true;
     }
 
     pub fn token_error(&mut self, token: Token, message: String) {
-        if let TokenType::Eof = token.token_type {
-            self.report(token.line, String::from("at end"), message);
-        } else {
-            self.report(token.line, format!("at {}", String::from_utf8_lossy(&token.lexeme)), message);
+        self.report(token.span, message);
+    }
 
+    // Formats a diagnostic rustc-style: the message, its location, then the
+    // offending source line with the span underlined.
+    fn render(&self, kind: &str, span: Span, message: &str) -> String {
+        let mut rendered = format!("{}: {}", kind, message);
+        if span.line == 0 {
+            // Synthesized by the parser, there is no source to point at
+            rendered.push_str(&format!("\n --> {}", self.file_name));
+            return rendered;
         }
+
+        let Source { name, text: source } = &self.sources[span.source as usize];
+        let offset = span.offset.min(source.len());
+        let line_start = source[..offset]
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |position| position + 1);
+        let line_end = source[line_start..]
+            .iter()
+            .position(|&c| c == b'\n')
+            .map_or(source.len(), |position| line_start + position);
+        let text = String::from_utf8_lossy(&source[line_start..line_end]);
+
+        // Keep tabs so the underline lines up with the quoted source
+        let indent: String = String::from_utf8_lossy(&source[line_start..offset])
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let underline_end = span.end().min(line_end).max(offset);
+        let width = String::from_utf8_lossy(&source[offset..underline_end]).chars().count().max(1);
+        let underline = format!("^{}", "~".repeat(width - 1));
+
+        let gutter = span.line.to_string();
+        let padding = " ".repeat(gutter.len());
+        rendered.push_str(&format!("\n{}--> {}:{}:{}", padding, name, span.line, span.column));
+        rendered.push_str(&format!("\n{} |", padding));
+        rendered.push_str(&format!("\n{} | {}", gutter, text.trim_end_matches('\r')));
+        rendered.push_str(&format!("\n{} | {}{}", padding, indent, underline));
+        return rendered;
     }
+}
+
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".lox_history"))
 }
\ No newline at end of file
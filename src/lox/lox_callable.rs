@@ -1,12 +1,123 @@
-use super::interpreter::*;
-use super::object::*;
-use super::exception::*;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
 
-pub struct LoxCallable {
+use super::environment::Environment;
+use super::exception::Exception;
+use super::interpreter::Interpreter;
+use super::objects::*;
+use super::stmt::Function;
+use super::token::Token;
+
+pub trait LoxCallable: fmt::Display {
+    fn arity(&self) -> usize;
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, Exception>;
 }
 
-impl LoxCallable {
-    fn call(interpreter: Interpreter, arguments: Vec<Object>) -> Result<Object, Exception> {
+impl fmt::Debug for dyn LoxCallable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+pub struct LoxFunction {
+    declaration: Rc<Function>,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(declaration: Rc<Function>, closure: Rc<RefCell<Environment>>, is_initializer: bool) -> Self {
+        LoxFunction {
+            declaration: declaration,
+            closure: closure,
+            is_initializer: is_initializer,
+        }
+    }
+
+    pub fn bind(&self, instance: Object) -> LoxFunction {
+        let environment = Environment::new_child(Rc::clone(&self.closure));
+        environment.borrow_mut().define(b"this".to_vec(), instance);
+        LoxFunction::new(Rc::clone(&self.declaration), environment, self.is_initializer)
+    }
+
+    fn this(&self) -> Object {
+        self.closure.borrow().get_local(b"this").unwrap_or(Object::Nil)
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, Exception> {
+        let environment = Environment::new_child(Rc::clone(&self.closure));
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            environment.borrow_mut().define(param.lexeme.clone(), argument);
+        }
+        match interpreter.execute_block(self.declaration.body.clone(), environment) {
+            // An initializer always hands back the instance, even on an early 'return;'
+            Ok(_) | Err(Exception::Return(_)) if self.is_initializer => return Ok(self.this()),
+            Ok(_) => return Ok(Object::Nil),
+            Err(Exception::Return(value)) => return Ok(value),
+            Err(err) => return Err(err),
+        }
+    }
+}
 
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn {}>", self.declaration.name.to_string_lexeme())
     }
+}
+
+pub type NativeFn = dyn Fn(&mut Interpreter, Vec<Object>) -> Result<Object, Exception>;
+
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&mut Interpreter, Vec<Object>) -> Result<Object, Exception> + 'static,
+    {
+        NativeFunction {
+            name: String::from(name),
+            arity: arity,
+            function: Box::new(function),
+        }
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, Exception> {
+        (self.function)(interpreter, arguments)
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+// Wraps a built-in method of a list, map, range or string into a callable bound
+// to the value it was accessed on
+pub fn bind_method<R, T>(name: &Token, arity: usize, receiver: R, method: fn(&T, Vec<Object>) -> Result<Object, Exception>) -> Object
+where
+    R: std::borrow::Borrow<T> + 'static,
+    T: ?Sized + 'static,
+{
+    let native = NativeFunction::new(&name.to_string_lexeme(), arity, move |_interpreter: &mut Interpreter, arguments| {
+        method(std::borrow::Borrow::borrow(&receiver), arguments)
+    });
+    return Object::Callable(Rc::new(native));
 }
\ No newline at end of file
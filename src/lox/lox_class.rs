@@ -0,0 +1,66 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use super::exception::Exception;
+use super::interpreter::Interpreter;
+use super::lox_callable::{LoxCallable, LoxFunction};
+use super::lox_instance::LoxInstance;
+use super::objects::*;
+
+#[derive(Clone)]
+pub struct LoxClass {
+    pub name: String,
+    superclass: Option<Rc<LoxClass>>,
+    methods: Rc<HashMap<Vec<u8>, Rc<LoxFunction>>>,
+}
+
+impl LoxClass {
+    pub fn new(name: String, superclass: Option<Rc<LoxClass>>, methods: HashMap<Vec<u8>, Rc<LoxFunction>>) -> Self {
+        LoxClass {
+            name: name,
+            superclass: superclass,
+            methods: Rc::new(methods),
+        }
+    }
+
+    pub fn find_method(&self, name: &[u8]) -> Option<Rc<LoxFunction>> {
+        if let Some(method) = self.methods.get(name) {
+            return Some(Rc::clone(method));
+        }
+        if let Some(ref superclass) = self.superclass {
+            return superclass.find_method(name);
+        }
+        return None;
+    }
+}
+
+impl LoxCallable for LoxClass {
+    fn arity(&self) -> usize {
+        match self.find_method(b"init") {
+            Some(initializer) => initializer.arity(),
+            None => 0,
+        }
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, Exception> {
+        let instance = Object::Instance(Rc::new(RefCell::new(LoxInstance::new(self.clone()))));
+        if let Some(initializer) = self.find_method(b"init") {
+            initializer.bind(instance.clone()).call(interpreter, arguments)?;
+        }
+        return Ok(instance);
+    }
+}
+
+impl fmt::Display for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl fmt::Debug for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use super::exception::Exception;
+use super::lox_class::LoxClass;
+use super::objects::*;
+use super::token::Token;
+
+pub struct LoxInstance {
+    class: LoxClass,
+    fields: HashMap<Vec<u8>, Object>,
+}
+
+impl LoxInstance {
+    pub fn new(class: LoxClass) -> Self {
+        LoxInstance {
+            class: class,
+            fields: HashMap::new(),
+        }
+    }
+
+    // Takes the shared handle because methods are bound to the instance itself
+    pub fn get(instance: &Rc<RefCell<LoxInstance>>, name: &Token) -> Result<Object, Exception> {
+        if let Some(value) = instance.borrow().fields.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        let method = instance.borrow().class.find_method(&name.lexeme);
+        if let Some(method) = method {
+            let bound = method.bind(Object::Instance(Rc::clone(instance)));
+            return Ok(Object::Callable(Rc::new(bound)));
+        }
+
+        let message = format!("Undefined property '{}'.", name.to_string_lexeme());
+        return Err(Exception::error(name.clone(), message));
+    }
+
+    pub fn set(&mut self, name: &Token, value: Object) {
+        self.fields.insert(name.lexeme.clone(), value);
+    }
+}
+
+impl fmt::Display for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
+
+impl fmt::Debug for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,103 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::vec;
+
+use num_bigint::BigInt;
+
+use super::exception::Exception;
+use super::interpreter::Interpreter;
+use super::lox_instance::LoxInstance;
+use super::lox_range::LoxRange;
+use super::objects::*;
+use super::token::{Literal, Token};
+use super::token_type::TokenType;
+
+// What a 'for (var x in ...)' loop walks over. Lox objects take part through a
+// protocol: an 'iterator()' method returning an object with 'hasNext()' and
+// 'next()', or those two methods on the object itself.
+pub enum LoxIterator {
+    // Lists are read as the loop goes, so elements pushed inside it are visited too
+    List { list: Rc<RefCell<Vec<Object>>>, index: usize },
+    Values(vec::IntoIter<Object>),
+    Range { range: LoxRange, next: BigInt },
+    Protocol(Rc<RefCell<LoxInstance>>),
+}
+
+impl LoxIterator {
+    // 'keyword' is the loop's 'in', where iteration errors are reported
+    pub fn new(interpreter: &mut Interpreter, iterable: Object, keyword: &Token) -> Result<Self, Exception> {
+        match iterable {
+            Object::List(list) => return Ok(LoxIterator::List { list: list, index: 0 }),
+            // Keys are taken up front, so the map can be changed inside the loop
+            Object::Map(map) => return Ok(LoxIterator::Values(map.borrow().keys().into_iter())),
+            Object::Range(range) => {
+                let next = range.start.clone();
+                return Ok(LoxIterator::Range { range: range, next: next });
+            }
+            Object::String(string) => {
+                let characters: Vec<Object> = string.chars().map(|c| Object::String(c.to_string())).collect();
+                return Ok(LoxIterator::Values(characters.into_iter()));
+            }
+            Object::Instance(instance) => {
+                if let Ok(method) = LoxInstance::get(&instance, &method_name("iterator", keyword)) {
+                    match call_method(interpreter, method, "iterator", keyword)? {
+                        Object::Instance(iterator) => return Ok(LoxIterator::Protocol(iterator)),
+                        _ => return Err(error(keyword, "'iterator()' must return an instance.")),
+                    }
+                }
+                if LoxInstance::get(&instance, &method_name("hasNext", keyword)).is_ok() {
+                    return Ok(LoxIterator::Protocol(instance));
+                }
+            }
+            _ => (),
+        }
+        return Err(error(keyword, "Can only iterate over lists, maps, strings, ranges and iterators."));
+    }
+
+    pub fn next(&mut self, interpreter: &mut Interpreter, keyword: &Token) -> Result<Option<Object>, Exception> {
+        match self {
+            LoxIterator::List { list, index } => {
+                let element = list.borrow().get(*index).cloned();
+                *index += 1;
+                return Ok(element);
+            }
+            LoxIterator::Values(values) => return Ok(values.next()),
+            LoxIterator::Range { range, next } => {
+                if !range.in_bounds(next) {
+                    return Ok(None);
+                }
+                let value = next.clone();
+                *next += &range.step;
+                return Ok(Some(Object::Int(value)));
+            }
+            LoxIterator::Protocol(iterator) => {
+                let has_next = LoxInstance::get(iterator, &method_name("hasNext", keyword))?;
+                let has_next = call_method(interpreter, has_next, "hasNext", keyword)?;
+                if !interpreter.is_truthy(&has_next) {
+                    return Ok(None);
+                }
+                let next = LoxInstance::get(iterator, &method_name("next", keyword))?;
+                return Ok(Some(call_method(interpreter, next, "next", keyword)?));
+            }
+        }
+    }
+}
+
+fn method_name(name: &str, keyword: &Token) -> Token {
+    Token::new(TokenType::Identifier, name.as_bytes().to_vec(), keyword.span, Literal::None)
+}
+
+fn call_method(interpreter: &mut Interpreter, method: Object, name: &str, keyword: &Token) -> Result<Object, Exception> {
+    let method = match method {
+        Object::Callable(method) if method.arity() == 0 => method,
+        _ => return Err(error(keyword, &format!("'{}' must be a method without parameters.", name))),
+    };
+    match method.call(interpreter, vec![]) {
+        Err(Exception::Native(message)) => return Err(error(keyword, &message)),
+        result => return result,
+    }
+}
+
+fn error(keyword: &Token, message: &str) -> Exception {
+    Exception::error(keyword.clone(), String::from(message))
+}
\ No newline at end of file
@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive};
+
+use super::exception::Exception;
+use super::lox_callable::bind_method;
+use super::lox_range::LoxRange;
+use super::objects::*;
+use super::token::Token;
+
+type ListMethod = fn(&Rc<RefCell<Vec<Object>>>, Vec<Object>) -> Result<Object, Exception>;
+
+pub fn get(list: &Rc<RefCell<Vec<Object>>>, name: &Token) -> Result<Object, Exception> {
+    let (arity, method): (usize, ListMethod) = match name.lexeme.as_slice() {
+        b"len" => (0, len),
+        b"push" => (1, push),
+        b"pop" => (0, pop),
+        b"insert" => (2, insert),
+        b"remove" => (1, remove),
+        b"slice" => (2, slice),
+        b"contains" => (1, contains),
+        b"sort" => (0, sort),
+        _ => {
+            let message = format!("Undefined property '{}'.", name.to_string_lexeme());
+            return Err(Exception::error(name.clone(), message));
+        }
+    };
+    return Ok(bind_method(name, arity, Rc::clone(list), method));
+}
+
+// Maps a possibly negative index onto 0..len, counting from the end when negative.
+// Also used for the characters of strings.
+pub fn index(len: usize, index: &Object) -> Result<usize, String> {
+    let index = match index {
+        Object::Int(index) => index,
+        _ => return Err(String::from("Index must be an integer.")),
+    };
+    let position = if index.is_negative() {
+        index + BigInt::from(len)
+    } else {
+        index.clone()
+    };
+    match position.to_usize() {
+        Some(position) if position < len => return Ok(position),
+        _ => return Err(String::from("Index out of range.")),
+    }
+}
+
+pub fn to_string(list: &[Object]) -> String {
+    let elements: Vec<String> = list.iter().map(|element| element.repr()).collect();
+    format!("[{}]", elements.join(", "))
+}
+
+fn len(list: &Rc<RefCell<Vec<Object>>>, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::Int(BigInt::from(list.borrow().len())));
+}
+
+fn push(list: &Rc<RefCell<Vec<Object>>>, arguments: Vec<Object>) -> Result<Object, Exception> {
+    list.borrow_mut().extend(arguments);
+    return Ok(Object::Nil);
+}
+
+fn pop(list: &Rc<RefCell<Vec<Object>>>, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    match list.borrow_mut().pop() {
+        Some(value) => return Ok(value),
+        None => return Err(Exception::Native(String::from("Can't pop from an empty list."))),
+    }
+}
+
+fn insert(list: &Rc<RefCell<Vec<Object>>>, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let mut arguments = arguments.into_iter();
+    let position = arguments.next().unwrap();
+    let value = arguments.next().unwrap();
+    let len = list.borrow().len();
+    // Inserting right after the last element is allowed, so check against one past the end
+    let position = index(len + 1, &position).map_err(Exception::Native)?;
+    list.borrow_mut().insert(position, value);
+    return Ok(Object::Nil);
+}
+
+fn remove(list: &Rc<RefCell<Vec<Object>>>, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let len = list.borrow().len();
+    let position = index(len, &arguments[0]).map_err(Exception::Native)?;
+    return Ok(list.borrow_mut().remove(position));
+}
+
+// Like Python slicing: bounds may be negative and are clamped to the list
+fn slice(list: &Rc<RefCell<Vec<Object>>>, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let list = list.borrow();
+    let start = clamp(list.len(), &arguments[0])?;
+    let end = clamp(list.len(), &arguments[1])?;
+    let elements = if start < end { list[start..end].to_vec() } else { vec![] };
+    return Ok(Object::List(Rc::new(RefCell::new(elements))));
+}
+
+// The elements a range picks out, as in 'xs[1..3]' or 'xs[(0..10).step(2)]'
+pub fn slice_range<T: Clone>(list: &[T], range: &LoxRange) -> Result<Vec<T>, String> {
+    if !range.step.is_positive() {
+        return Err(String::from("Slice step must be positive."));
+    }
+    let start = clamp_position(list.len(), &range.start, false);
+    let end = clamp_position(list.len(), &range.end, range.inclusive);
+    let step = range.step.to_usize().unwrap_or(usize::MAX);
+    if start >= end {
+        return Ok(vec![]);
+    }
+    return Ok(list[start..end].iter().step_by(step).cloned().collect());
+}
+
+fn clamp(len: usize, bound: &Object) -> Result<usize, Exception> {
+    match bound {
+        Object::Int(bound) => return Ok(clamp_position(len, bound, false)),
+        _ => return Err(Exception::Native(String::from("Slice bounds must be integers."))),
+    }
+}
+
+// 'past' moves one beyond the bound, for the end of an inclusive range
+fn clamp_position(len: usize, bound: &BigInt, past: bool) -> usize {
+    let len = BigInt::from(len);
+    let position = if bound.is_negative() { bound + &len } else { bound.clone() };
+    let position = if past { position + 1 } else { position };
+    let position = position.clamp(BigInt::from(0), len);
+    return position.to_usize().unwrap();
+}
+
+fn is_nan(object: &Object) -> bool {
+    return matches!(object, Object::Number(number) if number.is_nan());
+}
+
+fn contains(list: &Rc<RefCell<Vec<Object>>>, arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::Boolean(list.borrow().contains(&arguments[0])));
+}
+
+// Sorts in place; the elements must be all numbers or all strings
+fn sort(list: &Rc<RefCell<Vec<Object>>>, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    let mut list = list.borrow_mut();
+    let numbers = list.iter().all(|element| element.is_number());
+    let strings = list.iter().all(|element| element.is_string());
+    if !numbers && !strings {
+        return Err(Exception::Native(String::from("Can only sort lists of numbers or of strings.")));
+    }
+    list.sort_by(|left, right| match (left, right) {
+        (Object::String(left), Object::String(right)) => left.cmp(right),
+        // NaN compares with nothing, so it is ordered after every other number
+        _ => compare_numbers(left, right).unwrap_or_else(|| is_nan(left).cmp(&is_nan(right))),
+    });
+    return Ok(Object::Nil);
+}
\ No newline at end of file
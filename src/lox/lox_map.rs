@@ -0,0 +1,225 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
+
+use super::exception::Exception;
+use super::lox_callable::bind_method;
+use super::objects::*;
+use super::token::Token;
+
+// What a key hashes as. Keys that compare equal in Lox must map to the same
+// MapKey, so integral floats share the representation of the integer they equal
+// and callables, classes and instances hash by identity.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum MapKey {
+    Nil,
+    Boolean(bool),
+    String(String),
+    Int(BigInt),
+    Number(u64),
+    Identity(usize),
+}
+
+fn map_key(object: &Object) -> Result<MapKey, String> {
+    match object {
+        Object::Nil => return Ok(MapKey::Nil),
+        Object::Boolean(bool) => return Ok(MapKey::Boolean(*bool)),
+        Object::String(string) => return Ok(MapKey::String(string.clone())),
+        Object::Int(int) => return Ok(MapKey::Int(int.clone())),
+        Object::Number(number) => {
+            if number.is_nan() {
+                return Err(String::from("NaN can't be used as a map key."));
+            }
+            if number.is_finite() && number.fract() == 0.0 {
+                return Ok(MapKey::Int(BigInt::from_f64(*number).unwrap()));
+            }
+            return Ok(MapKey::Number(number.to_bits()));
+        }
+        Object::Callable(callable) => return Ok(MapKey::Identity(Rc::as_ptr(callable) as *const () as usize)),
+        Object::Class(class) => return Ok(MapKey::Identity(Rc::as_ptr(class) as usize)),
+        Object::Instance(instance) => return Ok(MapKey::Identity(Rc::as_ptr(instance) as usize)),
+        Object::List(_) => return Err(String::from("A list can't be used as a map key.")),
+        Object::Map(_) => return Err(String::from("A map can't be used as a map key.")),
+        Object::Range(_) => return Err(String::from("A range can't be used as a map key.")),
+    }
+}
+
+// Entries are kept in insertion order, which is the order keys() and printing use
+#[derive(Debug, Default)]
+pub struct LoxMap {
+    entries: Vec<(Object, Object)>,
+    indices: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        LoxMap::default()
+    }
+
+    pub fn get(&self, key: &Object) -> Result<Option<Object>, String> {
+        let key = map_key(key)?;
+        return Ok(self.indices.get(&key).map(|index| self.entries[*index].1.clone()));
+    }
+
+    pub fn insert(&mut self, key: Object, value: Object) -> Result<(), String> {
+        let hashed = map_key(&key)?;
+        match self.indices.get(&hashed) {
+            Some(index) => self.entries[*index].1 = value,
+            None => {
+                self.indices.insert(hashed, self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn remove(&mut self, key: &Object) -> Result<Option<Object>, String> {
+        let index = match self.indices.remove(&map_key(key)?) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let (_, value) = self.entries.remove(index);
+        for later in self.indices.values_mut() {
+            if *later > index {
+                *later -= 1;
+            }
+        }
+        return Ok(Some(value));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn keys(&self) -> Vec<Object> {
+        self.entries.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    pub fn values(&self) -> Vec<Object> {
+        self.entries.iter().map(|(_, value)| value.clone()).collect()
+    }
+
+    // Maps are equal when they hold equal values under the same keys, in any order
+    pub fn equals(&self, other: &LoxMap) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        return self.entries.iter().all(|(key, value)| match other.get(key) {
+            Ok(Some(other_value)) => *value == other_value,
+            _ => false,
+        });
+    }
+}
+
+impl fmt::Display for LoxMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key.repr(), value.repr()))
+            .collect();
+        write!(f, "{{{}}}", entries.join(", "))
+    }
+}
+
+type MapMethod = fn(&Rc<RefCell<LoxMap>>, Vec<Object>) -> Result<Object, Exception>;
+
+pub fn get(map: &Rc<RefCell<LoxMap>>, name: &Token) -> Result<Object, Exception> {
+    let (arity, method): (usize, MapMethod) = match name.lexeme.as_slice() {
+        b"len" => (0, len),
+        b"keys" => (0, keys),
+        b"values" => (0, values),
+        b"has" => (1, has),
+        b"remove" => (1, remove),
+        _ => {
+            let message = format!("Undefined property '{}'.", name.to_string_lexeme());
+            return Err(Exception::error(name.clone(), message));
+        }
+    };
+    return Ok(bind_method(name, arity, Rc::clone(map), method));
+}
+
+fn len(map: &Rc<RefCell<LoxMap>>, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::Int(BigInt::from(map.borrow().len())));
+}
+
+fn keys(map: &Rc<RefCell<LoxMap>>, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::List(Rc::new(RefCell::new(map.borrow().keys()))));
+}
+
+fn values(map: &Rc<RefCell<LoxMap>>, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::List(Rc::new(RefCell::new(map.borrow().values()))));
+}
+
+fn has(map: &Rc<RefCell<LoxMap>>, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let value = map.borrow().get(&arguments[0]).map_err(Exception::Native)?;
+    return Ok(Object::Boolean(value.is_some()));
+}
+
+fn remove(map: &Rc<RefCell<LoxMap>>, arguments: Vec<Object>) -> Result<Object, Exception> {
+    match map.borrow_mut().remove(&arguments[0]).map_err(Exception::Native)? {
+        Some(value) => return Ok(value),
+        None => return Err(Exception::Native(undefined_key(&arguments[0]))),
+    }
+}
+
+pub fn undefined_key(key: &Object) -> String {
+    format!("Undefined key {}.", key.repr())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(value: i64) -> Object {
+        Object::Int(BigInt::from(value))
+    }
+
+    // map_key must agree with '==': equal values share a key, unequal ones don't
+    fn assert_consistent(a: Object, b: Object) {
+        let same_key = map_key(&a).unwrap() == map_key(&b).unwrap();
+        assert_eq!(same_key, a == b, "{:?} and {:?}", a, b);
+    }
+
+    #[test]
+    fn integral_floats_share_the_key_of_their_integer() {
+        assert_consistent(int(1), Object::Number(1.0));
+        assert_consistent(int(1), Object::Number(1.5));
+        assert_consistent(Object::Number(1.5), Object::Number(1.5));
+        assert_consistent(Object::Number(1e300), Object::Number(1e300));
+    }
+
+    #[test]
+    fn negative_zero_is_zero() {
+        assert_consistent(Object::Number(-0.0), Object::Number(0.0));
+        assert_consistent(Object::Number(-0.0), int(0));
+    }
+
+    #[test]
+    fn different_kinds_never_share_a_key() {
+        assert_consistent(int(1), Object::String(String::from("1")));
+        assert_consistent(Object::Boolean(true), int(1));
+        assert_consistent(Object::Nil, Object::Boolean(false));
+    }
+
+    #[test]
+    fn nan_is_rejected() {
+        let nan = Object::Number(f64::NAN);
+        assert!(nan != nan.clone());
+        assert_eq!(map_key(&nan), Err(String::from("NaN can't be used as a map key.")));
+    }
+
+    #[test]
+    fn map_finds_entries_under_equal_keys() {
+        let mut map = LoxMap::new();
+        map.insert(int(1), Object::String(String::from("one"))).unwrap();
+        map.insert(Object::Number(1.0), Object::String(String::from("uno"))).unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&Object::Number(-0.0)).unwrap(), None);
+        assert_eq!(map.get(&int(1)).unwrap(), Some(Object::String(String::from("uno"))));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{FromPrimitive, Signed, Zero};
+
+use super::exception::Exception;
+use super::lox_callable::bind_method;
+use super::objects::*;
+use super::token::Token;
+
+// The integers from 'start' towards 'end', 'step' apart. Nothing is
+// materialized: membership and length are computed from the bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoxRange {
+    pub start: BigInt,
+    pub end: BigInt,
+    pub step: BigInt,
+    pub inclusive: bool,
+}
+
+impl LoxRange {
+    pub fn new(start: BigInt, end: BigInt, inclusive: bool) -> Self {
+        LoxRange {
+            start: start,
+            end: end,
+            step: BigInt::from(1),
+            inclusive: inclusive,
+        }
+    }
+
+    // Whether a value lies between the bounds, ignoring the step
+    pub fn in_bounds(&self, value: &BigInt) -> bool {
+        if self.step.is_positive() {
+            return *value >= self.start && (*value < self.end || (self.inclusive && *value == self.end));
+        }
+        return *value <= self.start && (*value > self.end || (self.inclusive && *value == self.end));
+    }
+
+    pub fn contains(&self, value: &BigInt) -> bool {
+        return self.in_bounds(value) && (value - &self.start).mod_floor(&self.step).is_zero();
+    }
+
+    pub fn len(&self) -> BigInt {
+        let step = self.step.abs();
+        let distance = if self.step.is_positive() {
+            &self.end - &self.start
+        } else {
+            &self.start - &self.end
+        };
+        // An inclusive range reaches one further
+        let distance = if self.inclusive { distance + 1 } else { distance };
+        if !distance.is_positive() {
+            return BigInt::zero();
+        }
+        return (distance + &step - 1) / step;
+    }
+}
+
+impl fmt::Display for LoxRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = if self.inclusive { "..=" } else { ".." };
+        if self.step == BigInt::from(1) {
+            return write!(f, "{}{}{}", self.start, operator, self.end);
+        }
+        write!(f, "({}{}{}).step({})", self.start, operator, self.end, self.step)
+    }
+}
+
+type RangeMethod = fn(&LoxRange, Vec<Object>) -> Result<Object, Exception>;
+
+pub fn get(range: &LoxRange, name: &Token) -> Result<Object, Exception> {
+    let (arity, method): (usize, RangeMethod) = match name.lexeme.as_slice() {
+        b"len" => (0, len),
+        b"contains" => (1, contains),
+        b"step" => (1, step),
+        _ => {
+            let message = format!("Undefined property '{}'.", name.to_string_lexeme());
+            return Err(Exception::error(name.clone(), message));
+        }
+    };
+    return Ok(bind_method(name, arity, range.clone(), method));
+}
+
+fn len(range: &LoxRange, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::Int(range.len()));
+}
+
+// Floats count as members when they hold an integral value, as they compare equal
+fn contains(range: &LoxRange, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let contained = match &arguments[0] {
+        Object::Int(value) => range.contains(value),
+        Object::Number(value) if value.fract() == 0.0 => match BigInt::from_f64(*value) {
+            Some(value) => range.contains(&value),
+            None => false,
+        },
+        _ => false,
+    };
+    return Ok(Object::Boolean(contained));
+}
+
+// A copy of the range counting in steps of the given size, downwards when negative
+fn step(range: &LoxRange, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let step = match &arguments[0] {
+        Object::Int(step) if !step.is_zero() => step.clone(),
+        _ => return Err(Exception::Native(String::from("Range step must be a non-zero integer."))),
+    };
+    let mut stepped = range.clone();
+    stepped.step = step;
+    return Ok(Object::Range(stepped));
+}
\ No newline at end of file
@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use super::exception::Exception;
+use super::lox_callable::bind_method;
+use super::lox_list;
+use super::lox_range::LoxRange;
+use super::objects::*;
+use super::token::Token;
+
+// Strings are immutable, so methods work on their own copy. Lengths and
+// positions count characters, not bytes.
+type StringMethod = fn(&str, Vec<Object>) -> Result<Object, Exception>;
+
+// Longest string, in bytes, that repeat() will build
+const MAX_REPEAT_LENGTH: usize = 1 << 28;
+
+pub fn get(string: &str, name: &Token) -> Result<Object, Exception> {
+    let (arity, method): (usize, StringMethod) = match name.lexeme.as_slice() {
+        b"len" => (0, len),
+        b"upper" => (0, upper),
+        b"lower" => (0, lower),
+        b"trim" => (0, trim),
+        b"split" => (1, split),
+        b"join" => (1, join),
+        b"replace" => (2, replace),
+        b"find" => (1, find),
+        b"starts_with" => (1, starts_with),
+        b"ends_with" => (1, ends_with),
+        b"substring" => (2, substring),
+        b"repeat" => (1, repeat),
+        b"chars" => (0, chars),
+        _ => {
+            let message = format!("Undefined property '{}'.", name.to_string_lexeme());
+            return Err(Exception::error(name.clone(), message));
+        }
+    };
+    return Ok(bind_method(name, arity, String::from(string), method));
+}
+
+fn len(string: &str, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::Int(BigInt::from(string.chars().count())));
+}
+
+fn upper(string: &str, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::String(string.to_uppercase()));
+}
+
+fn lower(string: &str, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::String(string.to_lowercase()));
+}
+
+fn trim(string: &str, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::String(String::from(string.trim())));
+}
+
+fn split(string: &str, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let separator = string_argument(&arguments[0])?;
+    if separator.is_empty() {
+        return Err(Exception::Native(String::from("Separator can't be empty.")));
+    }
+    let parts = string.split(separator).map(|part| Object::String(String::from(part))).collect();
+    return Ok(list(parts));
+}
+
+// The string goes between the elements: ", ".join(["a", "b"]) is "a, b"
+fn join(string: &str, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let elements = match &arguments[0] {
+        Object::List(elements) => elements,
+        _ => return Err(Exception::Native(String::from("Argument must be a list."))),
+    };
+    let parts: Vec<String> = elements.borrow().iter().map(|element| element.clone().to_string()).collect();
+    return Ok(Object::String(parts.join(string)));
+}
+
+fn replace(string: &str, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let from = string_argument(&arguments[0])?;
+    let to = string_argument(&arguments[1])?;
+    return Ok(Object::String(string.replace(from, to)));
+}
+
+// Character position of the first occurrence, or -1 when there is none
+fn find(string: &str, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let needle = string_argument(&arguments[0])?;
+    let position = match string.find(needle) {
+        Some(offset) => BigInt::from(string[..offset].chars().count()),
+        None => BigInt::from(-1),
+    };
+    return Ok(Object::Int(position));
+}
+
+fn starts_with(string: &str, arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::Boolean(string.starts_with(string_argument(&arguments[0])?)));
+}
+
+fn ends_with(string: &str, arguments: Vec<Object>) -> Result<Object, Exception> {
+    return Ok(Object::Boolean(string.ends_with(string_argument(&arguments[0])?)));
+}
+
+// Bounds behave like list slices: negative ones count from the end and both are clamped
+fn substring(string: &str, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let (start, end) = match (&arguments[0], &arguments[1]) {
+        (Object::Int(start), Object::Int(end)) => (start.clone(), end.clone()),
+        _ => return Err(Exception::Native(String::from("Substring bounds must be integers."))),
+    };
+    let characters: Vec<char> = string.chars().collect();
+    let range = LoxRange::new(start, end, false);
+    let characters = lox_list::slice_range(&characters, &range).map_err(Exception::Native)?;
+    return Ok(Object::String(characters.into_iter().collect()));
+}
+
+fn repeat(string: &str, arguments: Vec<Object>) -> Result<Object, Exception> {
+    let count = match &arguments[0] {
+        Object::Int(count) => count.to_usize(),
+        _ => None,
+    };
+    let count = match count {
+        Some(count) => count,
+        None => return Err(Exception::Native(String::from("Repeat count must be a non-negative integer."))),
+    };
+    match string.len().checked_mul(count) {
+        Some(length) if length <= MAX_REPEAT_LENGTH => return Ok(Object::String(string.repeat(count))),
+        _ => return Err(Exception::Native(String::from("Repeated string is too long."))),
+    }
+}
+
+fn chars(string: &str, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    let characters = string.chars().map(|character| Object::String(character.to_string())).collect();
+    return Ok(list(characters));
+}
+
+fn string_argument(argument: &Object) -> Result<&str, Exception> {
+    match argument {
+        Object::String(string) => return Ok(string),
+        _ => return Err(Exception::Native(String::from("Argument must be a string."))),
+    }
+}
+
+fn list(elements: Vec<Object>) -> Object {
+    Object::List(Rc::new(RefCell::new(elements)))
+}
\ No newline at end of file
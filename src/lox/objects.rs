@@ -1,64 +1,218 @@
-#[derive(PartialEq, Debug, Clone)]
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use super::lox_callable::LoxCallable;
+use super::lox_class::LoxClass;
+use super::lox_instance::LoxInstance;
+use super::lox_list;
+use super::lox_map::LoxMap;
+use super::lox_range::LoxRange;
+
+// A list or map, by address, being printed or compared further up the stack.
+// They are shared by reference, so one can end up holding itself.
+#[derive(PartialEq)]
+enum Visit {
+    Print(usize),
+    Compare(usize, usize),
+}
+
+thread_local! {
+    static VISITING: RefCell<Vec<Visit>> = const { RefCell::new(vec![]) };
+}
+
+// Runs 'f', or returns None when the same visit is already under way, so that
+// walking a cycle stops where it comes back around
+fn visit<T>(visit: Visit, f: impl FnOnce() -> T) -> Option<T> {
+    if VISITING.with(|visiting| visiting.borrow().contains(&visit)) {
+        return None;
+    }
+    VISITING.with(|visiting| visiting.borrow_mut().push(visit));
+    let result = f();
+    VISITING.with(|visiting| visiting.borrow_mut().pop());
+    return Some(result);
+}
+
+#[derive(Debug, Clone)]
 pub enum Object {
     Boolean(bool),
     String(String),
-    Number(f32),
+    Number(f64),
+    Int(BigInt),
+    Callable(Rc<dyn LoxCallable>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
+    List(Rc<RefCell<Vec<Object>>>),
+    Map(Rc<RefCell<LoxMap>>),
+    Range(LoxRange),
     Nil,
 }
 
-impl Object {
-    pub fn to_string(self) -> String {
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Object::Number(number) => number.to_string(),
-            Object::String(string) => string,
-            Object::Boolean(bool) => if bool {
-                String::from("true")
-            } else {
-                String::from("false")
-            },
-            Object::Nil => String::from("nil"),
+            Object::Number(number) => write!(f, "{}", format_number(*number)),
+            Object::Int(int) => write!(f, "{}", int),
+            Object::String(string) => write!(f, "{}", string),
+            Object::Boolean(bool) => write!(f, "{}", bool),
+            Object::Callable(callable) => write!(f, "{}", callable),
+            Object::Class(class) => write!(f, "{}", class),
+            Object::Instance(instance) => write!(f, "{}", instance.borrow()),
+            // A list or map inside itself prints as '[...]' or '{...}', as in Python
+            Object::List(list) => {
+                let text = visit(Visit::Print(Rc::as_ptr(list) as usize), || lox_list::to_string(&list.borrow()));
+                write!(f, "{}", text.as_deref().unwrap_or("[...]"))
+            }
+            Object::Map(map) => {
+                let text = visit(Visit::Print(Rc::as_ptr(map) as usize), || map.borrow().to_string());
+                write!(f, "{}", text.as_deref().unwrap_or("{...}"))
+            }
+            Object::Range(range) => write!(f, "{}", range),
+            Object::Nil => write!(f, "nil"),
         }
     }
-    pub fn bool(self) -> bool {
-        if let Object::Boolean(bool) = self {
-            bool
-        } else {
-            // Unreachable
-            panic!("Not a bool!");
+}
+
+impl Object {
+    // How a value looks inside a printed list or map, where strings are quoted
+    pub fn repr(&self) -> String {
+        match self {
+            Object::String(string) => format!("\"{}\"", string),
+            _ => self.to_string(),
         }
     }
 
     pub fn is_string(&self) -> bool {
-        if let Object::String(s) = self {
+        if let Object::String(_) = self {
             return true
         } else {
             return false
         }
     }
 
-    pub fn string(self) -> String {
-        if let Object::String(string) = self {
-            string
-        } else {
+    // Integers are widened, so this is the value either kind of number takes in float arithmetic
+    pub fn number(self) -> f64 {
+        match self {
+            Object::Number(number) => number,
+            Object::Int(int) => int.to_f64().unwrap_or(f64::NAN),
             // Unreachable
-            panic!("Not a string!");
+            _ => panic!("Not a number!"),
         }
     }
-    
-    pub fn number(self) -> f32 {
-        if let Object::Number(number) = self {
-            number
+
+    pub fn int(self) -> BigInt {
+        if let Object::Int(int) = self {
+            int
         } else {
             // Unreachable
-            panic!("Not a number!");
+            panic!("Not an integer!");
         }
     }
 
     pub fn is_number(&self) -> bool {
-        if let Object::Number(s) = self {
-            return true
-        } else {
-            return false
+        match self {
+            Object::Number(_) | Object::Int(_) => return true,
+            _ => return false,
         }
     }
+
+}
+
+// Prints numbers the way jlox does through Java's Double.toString, minus a
+// trailing ".0": plain decimals between 1e-3 and 1e7, scientific notation outside
+pub fn format_number(number: f64) -> String {
+    if number.is_nan() {
+        return String::from("NaN");
+    }
+    if number.is_infinite() {
+        return String::from(if number > 0.0 { "Infinity" } else { "-Infinity" });
+    }
+    let magnitude = number.abs();
+    if magnitude == 0.0 || (1e-3..1e7).contains(&magnitude) {
+        // Rust already prints the shortest round-tripping digits without ".0"
+        return number.to_string();
+    }
+    let scientific = format!("{:e}", number);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    if mantissa.contains('.') {
+        return format!("{}E{}", mantissa, exponent);
+    }
+    return format!("{}.0E{}", mantissa, exponent);
+}
+
+// Integers compare exactly with each other, and with floats holding an integral value
+pub fn compare_numbers(left: &Object, right: &Object) -> Option<Ordering> {
+    match (left, right) {
+        (Object::Int(left), Object::Int(right)) => return Some(left.cmp(right)),
+        (Object::Int(int), Object::Number(number)) => return compare_int_float(int, *number),
+        (Object::Number(number), Object::Int(int)) => {
+            return compare_int_float(int, *number).map(Ordering::reverse);
+        }
+        (Object::Number(left), Object::Number(right)) => return left.partial_cmp(right),
+        _ => return None,
+    }
 }
+
+fn compare_int_float(int: &BigInt, number: f64) -> Option<Ordering> {
+    if number.is_finite() && number.fract() == 0.0 {
+        return Some(int.cmp(&BigInt::from_f64(number).unwrap()));
+    }
+    return int.to_f64().unwrap_or(f64::NAN).partial_cmp(&number);
+}
+
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Object::Boolean(left), Object::Boolean(right)) => left == right,
+            (Object::String(left), Object::String(right)) => left == right,
+            (Object::Number(_), Object::Number(_))
+            | (Object::Int(_), Object::Int(_))
+            | (Object::Int(_), Object::Number(_))
+            | (Object::Number(_), Object::Int(_)) => compare_numbers(self, other) == Some(Ordering::Equal),
+            // Callables compare by identity
+            (Object::Callable(left), Object::Callable(right)) => Rc::ptr_eq(left, right),
+            (Object::Class(left), Object::Class(right)) => Rc::ptr_eq(left, right),
+            (Object::Instance(left), Object::Instance(right)) => Rc::ptr_eq(left, right),
+            // Lists are shared by reference but compare by their elements. Comparing two
+            // lists again while they are already being compared means both are cyclic in
+            // the same way, and nothing found so far tells them apart.
+            (Object::List(left), Object::List(right)) => {
+                let pair = Visit::Compare(Rc::as_ptr(left) as usize, Rc::as_ptr(right) as usize);
+                Rc::ptr_eq(left, right) || visit(pair, || *left.borrow() == *right.borrow()).unwrap_or(true)
+            }
+            (Object::Range(left), Object::Range(right)) => left == right,
+            (Object::Map(left), Object::Map(right)) => {
+                let pair = Visit::Compare(Rc::as_ptr(left) as usize, Rc::as_ptr(right) as usize);
+                Rc::ptr_eq(left, right) || visit(pair, || left.borrow().equals(&right.borrow())).unwrap_or(true)
+            }
+            (Object::Nil, Object::Nil) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_number_matches_jlox() {
+        assert_eq!(format_number(1e7), "1.0E7");
+        assert_eq!(format_number(1.5e300), "1.5E300");
+        assert_eq!(format_number(1e-4), "1.0E-4");
+        assert_eq!(format_number(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(format_number(100.0), "100");
+        assert_eq!(format_number(-2.5), "-2.5");
+    }
+
+    #[test]
+    fn format_number_special_values() {
+        assert_eq!(format_number(f64::NAN), "NaN");
+        assert_eq!(format_number(f64::INFINITY), "Infinity");
+        assert_eq!(format_number(f64::NEG_INFINITY), "-Infinity");
+    }
+}
\ No newline at end of file
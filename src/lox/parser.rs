@@ -1,7 +1,10 @@
+use std::mem;
 use std::vec;
 
+use num_bigint::BigInt;
+
 use super::expr::*;
-use super::stmt::Statement;
+use super::stmt::{Function, Statement};
 use super::token::*;
 use super::token::Literal as Lit;
 use super::token_type::*;
@@ -10,15 +13,19 @@ use super::objects::*;
 pub struct Parser<'a> {
     tokens: &'a Vec<Token>,
     current: usize,
-    loop_counter: u32,
+    // Labels of the loops enclosing the current statement, innermost last
+    loops: Vec<Option<Token>>,
+    // Positions of the tokens whose doc comments went to a declaration
+    documented: Vec<usize>,
 }
 
 impl Parser<'_> {
-    pub fn new(tokens: &Vec<Token>) -> Parser {
+    pub fn new(tokens: &Vec<Token>) -> Parser<'_> {
         Parser {
             tokens: tokens,
             current: 0,
-            loop_counter: 0,
+            loops: vec![],
+            documented: vec![],
         }
     }
     
@@ -31,12 +38,23 @@ impl Parser<'_> {
                 Err(err) => errors.push(err),
             }
         }
-        if errors.len() > 0 {
+        if errors.is_empty() {
+            errors = self.undocumented();
+        }
+        if !errors.is_empty() {
             return Err(errors);
         }
         return Ok(statements);
     }
 
+    pub fn parse_expression(&mut self) -> Result<Expr, (Token, String)> {
+        let expr = self.expression()?;
+        if !self.is_at_end() {
+            return Err((self.peek().clone(), String::from("Expect end of expression.")));
+        }
+        return Ok(expr);
+    }
+
     fn expression(&mut self) -> Result<Expr, (Token, String)> {
         return self.assignment();
     }
@@ -54,12 +72,46 @@ impl Parser<'_> {
                     let name = variable.name;
                     return Ok(Expr::assign(name, value));
                 }
+                Expr::Get(get) => {
+                    return Ok(Expr::set(*get.object, get.name, value));
+                }
+                Expr::Index(index) => {
+                    return Ok(Expr::set_index(*index.object, index.bracket, *index.index, value));
+                }
                 _ => return Err((equals, String::from("Invalid assignment target."))),
             }
         }
+        if self.mtch(vec![
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::PercentEqual,
+        ]) {
+            let operator: Token = self.previous().clone();
+            let value = self.assignment()?;
+            if !Self::is_assignable(&expr) {
+                return Err((operator, String::from("Invalid assignment target.")));
+            }
+            return Ok(Expr::update(expr, operator, value, false));
+        }
         return Ok(expr);
     }
 
+    fn is_assignable(expr: &Expr) -> bool {
+        return matches!(expr, Expr::Variable(_) | Expr::Get(_) | Expr::Index(_));
+    }
+
+    // '++'/'--' add or subtract one, so the right operand is a synthesized literal
+    fn increment(&mut self, target: Expr, operator: Token, postfix: bool) -> Result<Expr, (Token, String)> {
+        if !Self::is_assignable(&target) {
+            let message = format!("Invalid {} target.", if postfix { "postfix" } else { "prefix" });
+            return Err((operator, message));
+        }
+        let one = Expr::literal(Object::Int(BigInt::from(1)), Span::default());
+        return Ok(Expr::update(target, operator, one, postfix));
+    }
+
     fn or(&mut self) -> Result<Expr, (Token, String)> {
         let mut expr = match self.and() {
             Ok(expr) => expr,
@@ -87,11 +139,17 @@ impl Parser<'_> {
     }
 
     fn declaration(&mut self) -> Result<Statement, (Token, String)> {
-        if self.mtch(vec![TokenType::Var]) {
-            return self.var_declaration();
-        }
+        let statement = if self.mtch(vec![TokenType::Class]) {
+            self.class_declaration()
+        } else if self.mtch(vec![TokenType::Fun]) {
+            self.function(String::from("function")).map(Statement::Function)
+        } else if self.mtch(vec![TokenType::Var]) {
+            self.var_declaration()
+        } else {
+            self.statement()
+        };
 
-        match self.statement() {
+        match statement {
             Ok(statement) => return Ok(statement),
             Err(err) => {
                 self.synchronize();
@@ -100,9 +158,61 @@ impl Parser<'_> {
         }
     }
 
+    fn class_declaration(&mut self) -> Result<Statement, (Token, String)> {
+        let name = self.declaration_name(String::from("Expect class name."))?;
+
+        let mut superclass = Expr::Null;
+        if self.mtch(vec![TokenType::Less]) {
+            let superclass_name = self.consume(TokenType::Identifier, String::from("Expect superclass name."))?.clone();
+            if superclass_name.lexeme == name.lexeme {
+                return Err((superclass_name, String::from("A class can't inherit from itself.")));
+            }
+            superclass = Expr::variable(superclass_name);
+        }
+
+        self.consume(TokenType::LeftBrace, String::from("Expect '{' before class body."))?;
+
+        let mut methods: Vec<Function> = vec![];
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function(String::from("method"))?);
+        }
+
+        self.consume(TokenType::RightBrace, String::from("Expect '}' after class body."))?;
+        return Ok(Statement::class(name, superclass, methods));
+    }
+
+    fn function(&mut self, kind: String) -> Result<Function, (Token, String)> {
+        let name = self.declaration_name(format!("Expect {} name.", kind))?;
+        self.consume(TokenType::LeftParen, format!("Expect '(' after {} name.", kind))?;
+        let mut params: Vec<Token> = vec![];
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if params.len() >= 255 {
+                    return Err((self.peek().clone(), String::from("Can't have more than 255 parameters.")));
+                }
+                params.push(self.consume(TokenType::Identifier, String::from("Expect parameter name."))?.clone());
+                if !self.mtch(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, String::from("Expect ')' after parameters."))?;
+        self.consume(TokenType::LeftBrace, format!("Expect '{{' before {} body.", kind))?;
+
+        // 'break' and 'continue' can't cross a function boundary
+        let enclosing_loops = mem::take(&mut self.loops);
+        let body = self.block();
+        self.loops = enclosing_loops;
+
+        return Ok(Function { name: name, params: params, body: body? });
+    }
+
     fn statement(&mut self) -> Result<Statement, (Token, String)> {
+        if self.check(TokenType::Identifier) && self.check_next(TokenType::Colon) {
+            return self.labeled_statement();
+        }
         if self.mtch(vec![TokenType::For]) {
-            return self.for_statement();
+            return self.for_statement(None);
         }
         if self.mtch(vec![TokenType::If]) {
             return self.if_statement();
@@ -110,8 +220,11 @@ impl Parser<'_> {
         if self.mtch(vec![TokenType::Print]) {
             return self.print_statement();
         }
+        if self.mtch(vec![TokenType::Return]) {
+            return self.return_statement();
+        }
         if self.mtch(vec![TokenType::While]) {
-            return self.while_statement();
+            return self.while_statement(None);
         }
         if self.mtch(vec![TokenType::Break]) {
             return self.break_statement();
@@ -127,9 +240,33 @@ impl Parser<'_> {
         return self.expression_statement();
     }
 
-    fn for_statement(&mut self) -> Result<Statement, (Token, String)> {
+    fn labeled_statement(&mut self) -> Result<Statement, (Token, String)> {
+        let label = self.advance().clone();
+        self.advance();
+        if let Some(enclosing) = self.loops.iter().flatten().find(|enclosing| enclosing.lexeme == label.lexeme) {
+            let message = format!("Label '{}' is already used by an enclosing loop.", enclosing.to_string_lexeme());
+            return Err((label, message));
+        }
+        if self.mtch(vec![TokenType::For]) {
+            return self.for_statement(Some(label));
+        }
+        if self.mtch(vec![TokenType::While]) {
+            return self.while_statement(Some(label));
+        }
+        return Err((self.peek().clone(), String::from("Expect 'for' or 'while' after label.")));
+    }
+
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Statement, (Token, String)> {
         self.consume(TokenType::LeftParen, String::from("Expect '(' after 'for'."))?;
 
+        // 'for (var x in xs)', with 'var' optional since the variable is always new
+        if self.check(TokenType::Var) && self.check_ahead(2, TokenType::In) {
+            self.advance();
+        }
+        if self.check(TokenType::Identifier) && self.check_next(TokenType::In) {
+            return self.for_in_statement(label);
+        }
+
         let initializer;
         if self.mtch(vec![TokenType::Semicolon]) {
             initializer = Statement::Null;
@@ -153,27 +290,28 @@ impl Parser<'_> {
         
         self.consume(TokenType::RightParen, String::from("Expect ')' after for clauses."))?;
 
-        self.loop_counter += 1;
-        let mut body = self.statement()?;
-        self.loop_counter -= 1;
-
-        match increment {
-            Expr::Null => (),
-            _ => body = Statement::block(vec![body, Statement::expression(increment)]),
-        }
+        self.loops.push(label.clone());
+        let body = self.statement();
+        self.loops.pop();
 
         if let Expr::Null = condition {
-            condition = Expr::literal(Object::Boolean(true));
+            condition = Expr::literal(Object::Boolean(true), Span::default());
         }
 
-        body = Statement::while_branch(condition, body);
+        return Ok(Statement::for_loop(initializer, condition, increment, body?, label));
+    }
 
-        match initializer {
-            Statement::Null => (),
-            _ => body = Statement::block(vec![initializer, body]),
-        }
+    fn for_in_statement(&mut self, label: Option<Token>) -> Result<Statement, (Token, String)> {
+        let name = self.advance().clone();
+        let keyword = self.advance().clone();
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, String::from("Expect ')' after for-in clause."))?;
+
+        self.loops.push(label.clone());
+        let body = self.statement();
+        self.loops.pop();
 
-        return Ok(body);
+        return Ok(Statement::for_in(name, keyword, iterable, body?, label));
     }
 
     fn if_statement(&mut self) -> Result<Statement, (Token, String)> {
@@ -200,12 +338,19 @@ impl Parser<'_> {
         return Ok(Statement::print(unwraped_expr));
     }
 
+    fn return_statement(&mut self) -> Result<Statement, (Token, String)> {
+        let keyword = self.previous().clone();
+        let mut value = Expr::Null;
+        if !self.check(TokenType::Semicolon) {
+            value = self.expression()?;
+        }
+        self.consume(TokenType::Semicolon, String::from("Expect ';' after return value."))?;
+        return Ok(Statement::return_statement(keyword, value));
+    }
+
     fn var_declaration(&mut self) -> Result<Statement, (Token, String)> {
-        let name = match self.consume(TokenType::Identifier, String::from("Expect variable name")) {
-            Ok(token) => token.clone(),
-            Err(err) => return Err(err),
-        };
-        let mut initializer = Expr::literal(Object::Nil);
+        let name = self.declaration_name(String::from("Expect variable name"))?;
+        let mut initializer = Expr::literal(Object::Nil, Span::default());
         if self.mtch(vec![TokenType::Equal]) {
             match self.expression() {
                 Ok(expr) => {
@@ -218,7 +363,7 @@ impl Parser<'_> {
         return Ok(Statement::var(name, initializer));
     }
 
-    fn while_statement(&mut self) -> Result<Statement, (Token, String)> {
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Statement, (Token, String)> {
         self.consume(TokenType::LeftParen, String::from("Expect '(' after 'while'."))?;
         let condition = match self.expression() {
             Ok(statement) => statement,
@@ -228,13 +373,10 @@ impl Parser<'_> {
             Ok(_) => (),
             Err(err) => return Err(err),
         }
-        self.loop_counter += 1;
-        let body = match self.statement() {
-            Ok(statement) => statement,
-            Err(err) => return Err(err),
-        };
-        self.loop_counter -= 1;
-        return Ok(Statement::while_branch(condition, body));
+        self.loops.push(label.clone());
+        let body = self.statement();
+        self.loops.pop();
+        return Ok(Statement::while_branch(condition, body?, label));
         
     }
     
@@ -251,19 +393,40 @@ impl Parser<'_> {
     }
 
     fn break_statement(&mut self) -> Result<Statement, (Token, String)> {
-        if self.loop_counter > 0 {
-            self.consume(TokenType::Semicolon, String::from("Expect ';' after variable declaration."))?;
-            return Ok(Statement::Break);
+        let keyword = self.previous().clone();
+        if self.loops.is_empty() {
+            return Err((keyword, String::from("Use of 'break' not allowed.")));
         }
-        return Err((self.peek().clone(), String::from("Use of 'break' not allowed.")))
+        let (label, depth) = self.loop_target()?;
+        self.consume(TokenType::Semicolon, String::from("Expect ';' after 'break'."))?;
+        return Ok(Statement::break_statement(label, depth));
     }
 
     fn continue_statement(&mut self) -> Result<Statement, (Token, String)> {
-        if self.loop_counter > 0 {
-            self.consume(TokenType::Semicolon, String::from("Expect ';' after variable declaration."))?;
-            return Ok(Statement::Continue);
+        let keyword = self.previous().clone();
+        if self.loops.is_empty() {
+            return Err((keyword, String::from("Use of 'continue' not allowed.")));
+        }
+        let (label, depth) = self.loop_target()?;
+        self.consume(TokenType::Semicolon, String::from("Expect ';' after 'continue'."))?;
+        return Ok(Statement::continue_statement(label, depth));
+    }
+
+    // Resolves the optional label after 'break'/'continue' to how many loops out it jumps
+    fn loop_target(&mut self) -> Result<(Option<Token>, usize), (Token, String)> {
+        if !self.mtch(vec![TokenType::Identifier]) {
+            return Ok((None, 0));
+        }
+        let label = self.previous().clone();
+        for (depth, enclosing) in self.loops.iter().rev().enumerate() {
+            if let Some(enclosing) = enclosing {
+                if enclosing.lexeme == label.lexeme {
+                    return Ok((Some(label), depth));
+                }
+            }
         }
-        return Err((self.peek().clone(), String::from("Use of 'continue' not allowed.")))
+        let message = format!("Unknown loop label '{}'.", label.to_string_lexeme());
+        return Err((label, message));
     }
     
     fn block(&mut self) -> Result<Vec<Statement>, (Token, String)> {
@@ -318,13 +481,13 @@ impl Parser<'_> {
     }
 
     fn comparison(&mut self) -> Result<Expr, (Token, String)> {
-        let mut unwraped_expr = match self.term() {
+        let mut unwraped_expr = match self.range() {
             Ok(expr) => expr,
             Err(err) => return Err(err),
         };
         while self.mtch(vec![TokenType::Greater, TokenType::GreaterEqual, TokenType::Less, TokenType::LessEqual]) {
             let operator = self.previous().clone();
-            let right = self.term();
+            let right = self.range();
             match right {
                 Ok(expr) => unwraped_expr = Expr::binary(unwraped_expr, operator, expr),
                 Err(err) => return Err(err),
@@ -333,6 +496,58 @@ impl Parser<'_> {
         return Ok(unwraped_expr);
     }
 
+    // Ranges don't chain, and their bounds are full arithmetic: '0..n - 1'
+    fn range(&mut self) -> Result<Expr, (Token, String)> {
+        let start = self.bit_or()?;
+        if self.mtch(vec![TokenType::DotDot, TokenType::DotDotEqual]) {
+            let operator = self.previous().clone();
+            let end = self.bit_or()?;
+            return Ok(Expr::binary(start, operator, end));
+        }
+        return Ok(start);
+    }
+
+    // Bitwise operators bind tighter than comparisons, so 'a & b == 0' tests the masked value
+    fn bit_or(&mut self) -> Result<Expr, (Token, String)> {
+        let mut unwraped_expr = self.bit_xor()?;
+        while self.mtch(vec![TokenType::Pipe]) {
+            let operator = self.previous().clone();
+            let right = self.bit_xor()?;
+            unwraped_expr = Expr::binary(unwraped_expr, operator, right);
+        }
+        return Ok(unwraped_expr);
+    }
+
+    fn bit_xor(&mut self) -> Result<Expr, (Token, String)> {
+        let mut unwraped_expr = self.bit_and()?;
+        while self.mtch(vec![TokenType::Caret]) {
+            let operator = self.previous().clone();
+            let right = self.bit_and()?;
+            unwraped_expr = Expr::binary(unwraped_expr, operator, right);
+        }
+        return Ok(unwraped_expr);
+    }
+
+    fn bit_and(&mut self) -> Result<Expr, (Token, String)> {
+        let mut unwraped_expr = self.shift()?;
+        while self.mtch(vec![TokenType::Ampersand]) {
+            let operator = self.previous().clone();
+            let right = self.shift()?;
+            unwraped_expr = Expr::binary(unwraped_expr, operator, right);
+        }
+        return Ok(unwraped_expr);
+    }
+
+    fn shift(&mut self) -> Result<Expr, (Token, String)> {
+        let mut unwraped_expr = self.term()?;
+        while self.mtch(vec![TokenType::LessLess, TokenType::GreaterGreater]) {
+            let operator = self.previous().clone();
+            let right = self.term()?;
+            unwraped_expr = Expr::binary(unwraped_expr, operator, right);
+        }
+        return Ok(unwraped_expr);
+    }
+
     fn term(&mut self) -> Result<Expr, (Token, String)> {
         let mut unwraped_expr = match self.factor() {
             Ok(expr) => expr,
@@ -354,7 +569,7 @@ impl Parser<'_> {
             Ok(expr) => expr,
             Err(err) => return Err(err),
         };
-        while self.mtch(vec![TokenType::Slash, TokenType::Star]) {
+        while self.mtch(vec![TokenType::Slash, TokenType::TildeSlash, TokenType::Percent, TokenType::Star]) {
             let operator = self.previous().clone();
             let right = self.unary();
             match right {
@@ -366,9 +581,14 @@ impl Parser<'_> {
     }
 
     fn unary(&mut self) -> Result<Expr, (Token, String)> {
-        if self.mtch(vec![TokenType::Bang, TokenType::Minus]) {
+        if self.mtch(vec![TokenType::PlusPlus, TokenType::MinusMinus]) {
             let operator: Token = self.previous().clone();
-            let right = self.primary();
+            let target = self.unary()?;
+            return self.increment(target, operator, false);
+        }
+        if self.mtch(vec![TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
+            let operator: Token = self.previous().clone();
+            let right = self.unary();
             let right = match right {
                 Ok(expr) => expr,
                 Err(err) => return Err(err),
@@ -376,7 +596,28 @@ impl Parser<'_> {
             return Ok(Expr::unary(operator, right));
         }
 
-        return self.call();
+        return self.power();
+    }
+
+    // '**' binds tighter than a unary operator on its left and is right-associative,
+    // so '-2 ** 2' is -4 and '2 ** 3 ** 2' is 2 ** 9
+    fn power(&mut self) -> Result<Expr, (Token, String)> {
+        let base = self.postfix()?;
+        if self.mtch(vec![TokenType::StarStar]) {
+            let operator = self.previous().clone();
+            let exponent = self.unary()?;
+            return Ok(Expr::binary(base, operator, exponent));
+        }
+        return Ok(base);
+    }
+
+    fn postfix(&mut self) -> Result<Expr, (Token, String)> {
+        let expr = self.call()?;
+        if self.mtch(vec![TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator: Token = self.previous().clone();
+            return self.increment(expr, operator, true);
+        }
+        return Ok(expr);
     }
 
     fn finish_call(&mut self, callee: Expr) -> Result<Expr, (Token, String)> {
@@ -384,11 +625,10 @@ impl Parser<'_> {
         if !self.check(TokenType::RightParen) {
             loop {
                 if arguments.len() >= 255 {
-                    // return Err(self.peek().clone(), String::from("Can't have more than 255 arguments."));
-                    println!("Can't have more than 255 arguments.");
+                    return Err((self.peek().clone(), String::from("Can't have more than 255 arguments.")));
                 }
                 arguments.push(self.expression()?);
-                if self.mtch(vec![TokenType::Comma]) {
+                if !self.mtch(vec![TokenType::Comma]) {
                     break;
                 }
             }
@@ -404,6 +644,13 @@ impl Parser<'_> {
         loop {
             if self.mtch(vec![TokenType::LeftParen]) {
                 expr = self.finish_call(expr)?;
+            } else if self.mtch(vec![TokenType::Dot]) {
+                let name = self.consume(TokenType::Identifier, String::from("Expect property name after '.'."))?.clone();
+                expr = Expr::get(expr, name);
+            } else if self.mtch(vec![TokenType::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RightBracket, String::from("Expect ']' after index."))?.clone();
+                expr = Expr::index(expr, bracket, index);
             } else {
                 break;
             }
@@ -414,22 +661,43 @@ impl Parser<'_> {
 
     fn primary(&mut self) -> Result<Expr, (Token, String)> {
         if self.mtch(vec![TokenType::False]) {
-            return Ok(Expr::literal(Object::Boolean(false)));
+            return Ok(Expr::literal(Object::Boolean(false), self.previous().span));
         } 
         if self.mtch(vec![TokenType::True]) {
-            return Ok(Expr::literal(Object::Boolean(true)));
+            return Ok(Expr::literal(Object::Boolean(true), self.previous().span));
         }
         if self.mtch(vec![TokenType::Nil]) {
-            return Ok(Expr::literal(Object::Nil));
+            return Ok(Expr::literal(Object::Nil, self.previous().span));
         }
         if self.mtch(vec![TokenType::Number, TokenType::String]) {
+            let span = self.previous().span;
             match self.previous().literal.clone() {
-               Lit::String(s) => return Ok(Expr::literal(Object::String(s))),
-               Lit::Number(s) => return Ok(Expr::literal(Object::Number(s))),
+               Lit::String(s) => return Ok(Expr::literal(Object::String(s), span)),
+               Lit::Number(s) => return Ok(Expr::literal(Object::Number(s), span)),
+               Lit::Int(s) => return Ok(Expr::literal(Object::Int(s), span)),
                _ => (), // Unreacheble
             }
             
         }
+        if self.mtch(vec![TokenType::Interpolation]) {
+            return self.interpolation();
+        }
+        if self.mtch(vec![TokenType::LeftBracket]) {
+            return self.list();
+        }
+        // A '{' that starts a statement is a block, so here it can only open a map
+        if self.mtch(vec![TokenType::LeftBrace]) {
+            return self.map();
+        }
+        if self.mtch(vec![TokenType::Super]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenType::Dot, String::from("Expect '.' after 'super'."))?;
+            let method = self.consume(TokenType::Identifier, String::from("Expect superclass method name."))?.clone();
+            return Ok(Expr::super_expr(keyword, method));
+        }
+        if self.mtch(vec![TokenType::This]) {
+            return Ok(Expr::this(self.previous().clone()));
+        }
         if self.mtch(vec![TokenType::Identifier]) {
             return Ok(Expr::variable(self.previous().clone()));
         }
@@ -448,9 +716,69 @@ impl Parser<'_> {
         Err((self.peek().clone(), String::from("Expected expression."))) // Result<&Token, (u32, String, String)>
     }
 
+    // "a${b}c" becomes "a" + b + "c"; the leading string makes every '+' a concatenation
+    fn interpolation(&mut self) -> Result<Expr, (Token, String)> {
+        let mut expr = self.string_part();
+        loop {
+            let value = self.expression()?;
+            expr = Self::concatenate(expr, value);
+            if self.mtch(vec![TokenType::InterpolationMiddle]) {
+                expr = Self::concatenate(expr, self.string_part());
+                continue;
+            }
+            self.consume(TokenType::InterpolationEnd, String::from("Expect '}' after interpolated expression."))?;
+            return Ok(Self::concatenate(expr, self.string_part()));
+        }
+    }
+
+    fn string_part(&self) -> Expr {
+        let token = self.previous();
+        match &token.literal {
+            Lit::String(value) => return Expr::literal(Object::String(value.clone()), token.span),
+            // Unreachable
+            _ => return Expr::Null,
+        }
+    }
+
+    fn concatenate(left: Expr, right: Expr) -> Expr {
+        let plus = Token::new(TokenType::Plus, b"+".to_vec(), right.span(), Lit::None);
+        return Expr::binary(left, plus, right);
+    }
+
+    fn list(&mut self) -> Result<Expr, (Token, String)> {
+        let start = self.previous().span;
+        let mut elements: Vec<Expr> = vec![];
+        // A trailing comma is allowed so long literals can be split one element per line
+        while !self.check(TokenType::RightBracket) {
+            elements.push(self.expression()?);
+            if !self.mtch(vec![TokenType::Comma]) {
+                break;
+            }
+        }
+        let bracket = self.consume(TokenType::RightBracket, String::from("Expect ']' after list elements."))?;
+        return Ok(Expr::list(elements, start.to(bracket.span)));
+    }
+
+    fn map(&mut self) -> Result<Expr, (Token, String)> {
+        let start = self.previous().span;
+        let mut entries: Vec<(Expr, Expr)> = vec![];
+        while !self.check(TokenType::RightBrace) {
+            let key = self.expression()?;
+            self.consume(TokenType::Colon, String::from("Expect ':' after map key."))?;
+            let value = self.expression()?;
+            entries.push((key, value));
+            if !self.mtch(vec![TokenType::Comma]) {
+                break;
+            }
+        }
+        let brace = self.consume(TokenType::RightBrace, String::from("Expect '}' after map entries."))?;
+        let span = start.to(brace.span);
+        return Ok(Expr::map(entries, span));
+    }
+
     fn mtch(&mut self, types: Vec<TokenType>) -> bool {
         for token_type in types {
-            if (self.check(token_type)) {
+            if self.check(token_type) {
                 self.advance();
                 return true;
             }
@@ -465,6 +793,17 @@ impl Parser<'_> {
         return Err((self.peek().clone(), message))
     }
 
+    fn check_next(&self, token_type: TokenType) -> bool {
+        return self.check_ahead(1, token_type);
+    }
+
+    fn check_ahead(&self, distance: usize, token_type: TokenType) -> bool {
+        match self.tokens.get(self.current + distance) {
+            Some(token) => token.token_type == token_type,
+            None => false,
+        }
+    }
+
     fn check(&self, token_type: TokenType) -> bool {
         if self.is_at_end() {
             return false;
@@ -494,6 +833,34 @@ impl Parser<'_> {
         return &self.tokens[self.current];
     }
 
+    // Doc comments are attached to a declaration's first token, which is the name
+    // itself only for methods. Move them onto the name, where the tree keeps them.
+    fn declaration_name(&mut self, message: String) -> Result<Token, (Token, String)> {
+        let docs = match self.previous().token_type {
+            TokenType::Class | TokenType::Fun | TokenType::Var => self.previous().docs.clone(),
+            _ => None,
+        };
+        self.documented.push(self.current - 1);
+        let mut name = self.consume(TokenType::Identifier, message)?.clone();
+        self.documented.push(self.current - 1);
+        if name.docs.is_none() {
+            name.docs = docs;
+        }
+        return Ok(name);
+    }
+
+    // The scanner lets any identifier take doc comments, as it can't tell a method
+    // name from one in an expression. Those that no declaration claimed are misplaced.
+    fn undocumented(&self) -> Vec<(Token, String)> {
+        return self
+            .tokens
+            .iter()
+            .enumerate()
+            .filter(|(position, token)| !token.docs().is_empty() && !self.documented.contains(position))
+            .map(|(_, token)| (token.docs()[0].clone(), String::from("Doc comment must come before a declaration.")))
+            .collect();
+    }
+
     fn previous(&self) -> &Token {
         return &self.tokens[self.current - 1];
     }
@@ -501,7 +868,7 @@ impl Parser<'_> {
     fn synchronize(&mut self) {
         self.advance();
         while !self.is_at_end() {
-            if let TokenType::Semicolon = self.peek().token_type {
+            if let TokenType::Semicolon = self.previous().token_type {
                 return;
             }
             match self.peek().token_type {
@@ -0,0 +1,28 @@
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::environment::Environment;
+use super::exception::Exception;
+use super::interpreter::Interpreter;
+use super::lox_callable::NativeFunction;
+use super::objects::*;
+
+// Natives installed in the global environment of every interpreter
+pub fn define_globals(globals: &mut Environment) {
+    define_native(globals, "clock", 0, clock);
+}
+
+fn define_native<F>(globals: &mut Environment, name: &str, arity: usize, function: F)
+where
+    F: Fn(&mut Interpreter, Vec<Object>) -> Result<Object, Exception> + 'static,
+{
+    let native = NativeFunction::new(name, arity, function);
+    globals.define(name.as_bytes().to_vec(), Object::Callable(Rc::new(native)));
+}
+
+fn clock(_interpreter: &mut Interpreter, _arguments: Vec<Object>) -> Result<Object, Exception> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    return Ok(Object::Number(elapsed.as_secs_f64()));
+}
\ No newline at end of file
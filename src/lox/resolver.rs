@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use super::expr::*;
+use super::stmt::*;
+use super::token::Token;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+// Walks the syntax tree once before interpretation, storing on every local
+// variable access how many scopes away its declaration lives.
+pub struct Resolver {
+    scopes: Vec<HashMap<Vec<u8>, bool>>,
+    current_function: FunctionType,
+    current_class: ClassType,
+    errors: Vec<(Token, String)>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Resolver {
+            scopes: vec![],
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+            errors: vec![],
+        }
+    }
+
+    pub fn resolve(&mut self, statements: &mut Vec<Statement>) -> Result<(), Vec<(Token, String)>> {
+        self.resolve_statements(statements);
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+        return Ok(());
+    }
+
+    fn resolve_statements(&mut self, statements: &mut Vec<Statement>) {
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+    }
+
+    fn resolve_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::Block(block) => {
+                self.begin_scope();
+                self.resolve_statements(&mut block.statements);
+                self.end_scope();
+            }
+            Statement::Class(class) => self.visit_class_statement(class),
+            Statement::Expression(expression) => self.resolve_expr(&mut expression.expression),
+            Statement::For(for_loop) => {
+                // The initializer gets a scope of its own, just like at runtime
+                self.begin_scope();
+                self.resolve_statement(&mut for_loop.initializer);
+                self.resolve_expr(&mut for_loop.condition);
+                self.resolve_expr(&mut for_loop.increment);
+                self.resolve_statement(&mut for_loop.body);
+                self.end_scope();
+            }
+            Statement::ForIn(for_in) => {
+                self.resolve_expr(&mut for_in.iterable);
+                // Every iteration binds the variable in a fresh scope around the body
+                self.begin_scope();
+                self.declare(&for_in.name);
+                self.define(&for_in.name);
+                self.resolve_statement(&mut for_in.body);
+                self.end_scope();
+            }
+            Statement::Function(function) => {
+                self.declare(&function.name);
+                self.define(&function.name);
+                self.resolve_function(function, FunctionType::Function);
+            }
+            Statement::If(if_branch) => {
+                self.resolve_expr(&mut if_branch.condition);
+                self.resolve_statement(&mut if_branch.then_branch);
+                self.resolve_statement(&mut if_branch.else_branch);
+            }
+            Statement::Print(print) => self.resolve_expr(&mut print.expression),
+            Statement::Return(return_statement) => self.visit_return_statement(return_statement),
+            Statement::Var(var) => {
+                self.declare(&var.name);
+                self.resolve_expr(&mut var.initializer);
+                self.define(&var.name);
+            }
+            Statement::While(while_branch) => {
+                self.resolve_expr(&mut while_branch.condition);
+                self.resolve_statement(&mut while_branch.body);
+            }
+            Statement::Break(_) => (),
+            Statement::Continue(_) => (),
+            Statement::Null => (),
+        }
+    }
+
+    fn visit_class_statement(&mut self, class: &mut Class) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(&class.name);
+        self.define(&class.name);
+
+        let has_superclass = !matches!(*class.superclass, Expr::Null);
+        if has_superclass {
+            self.current_class = ClassType::Subclass;
+            self.resolve_expr(&mut class.superclass);
+            self.begin_scope();
+            self.scopes.last_mut().unwrap().insert(b"super".to_vec(), true);
+        }
+
+        self.begin_scope();
+        self.scopes.last_mut().unwrap().insert(b"this".to_vec(), true);
+        for method in class.methods.iter_mut() {
+            let declaration = if method.name.lexeme == b"init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            self.resolve_function(method, declaration);
+        }
+        self.end_scope();
+
+        if has_superclass {
+            self.end_scope();
+        }
+        self.current_class = enclosing_class;
+    }
+
+    fn visit_return_statement(&mut self, statement: &mut Return) {
+        if self.current_function == FunctionType::None {
+            self.error(&statement.keyword, "Can't return from top-level code.");
+        }
+        if let Expr::Null = *statement.value {
+            return;
+        }
+        if self.current_function == FunctionType::Initializer {
+            self.error(&statement.keyword, "Can't return a value from an initializer.");
+        }
+        self.resolve_expr(&mut statement.value);
+    }
+
+    fn resolve_function(&mut self, function: &mut Function, function_type: FunctionType) {
+        let enclosing_function = self.current_function;
+        self.current_function = function_type;
+
+        self.begin_scope();
+        for param in function.params.iter() {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve_statements(&mut function.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    fn resolve_expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Binary(binary) => {
+                self.resolve_expr(&mut binary.left);
+                self.resolve_expr(&mut binary.right);
+            }
+            Expr::Assign(assign) => {
+                self.resolve_expr(&mut assign.value);
+                assign.depth = self.resolve_local(&assign.name);
+            }
+            Expr::Grouping(grouping) => self.resolve_expr(&mut grouping.expression),
+            Expr::Literal(_) => (),
+            Expr::Logical(logical) => {
+                self.resolve_expr(&mut logical.left);
+                self.resolve_expr(&mut logical.right);
+            }
+            Expr::Ternary(ternary) => {
+                self.resolve_expr(&mut ternary.expression);
+                self.resolve_expr(&mut ternary.true_part);
+                self.resolve_expr(&mut ternary.false_part);
+            }
+            Expr::Unary(unary) => self.resolve_expr(&mut unary.right),
+            Expr::Variable(variable) => {
+                if let Some(scope) = self.scopes.last() {
+                    if scope.get(&variable.name.lexeme) == Some(&false) {
+                        self.error(&variable.name, "Can't read local variable in its own initializer.");
+                    }
+                }
+                variable.depth = self.resolve_local(&variable.name);
+            }
+            Expr::Update(update) => {
+                self.resolve_expr(&mut update.value);
+                self.resolve_expr(&mut update.target);
+            }
+            Expr::Call(call) => {
+                self.resolve_expr(&mut call.callee);
+                for argument in call.arguments.iter_mut() {
+                    self.resolve_expr(argument);
+                }
+            }
+            Expr::List(list) => {
+                for element in list.elements.iter_mut() {
+                    self.resolve_expr(element);
+                }
+            }
+            Expr::Map(map) => {
+                for (key, value) in map.entries.iter_mut() {
+                    self.resolve_expr(key);
+                    self.resolve_expr(value);
+                }
+            }
+            Expr::Index(index) => {
+                self.resolve_expr(&mut index.object);
+                self.resolve_expr(&mut index.index);
+            }
+            Expr::SetIndex(set_index) => {
+                self.resolve_expr(&mut set_index.value);
+                self.resolve_expr(&mut set_index.object);
+                self.resolve_expr(&mut set_index.index);
+            }
+            Expr::Get(get) => self.resolve_expr(&mut get.object),
+            Expr::Set(set) => {
+                self.resolve_expr(&mut set.value);
+                self.resolve_expr(&mut set.object);
+            }
+            Expr::Super(super_expr) => {
+                match self.current_class {
+                    ClassType::None => self.error(&super_expr.keyword, "Can't use 'super' outside of a class."),
+                    ClassType::Class => self.error(&super_expr.keyword, "Can't use 'super' in a class with no superclass."),
+                    ClassType::Subclass => (),
+                }
+                super_expr.depth = self.resolve_local(&super_expr.keyword);
+            }
+            Expr::This(this) => {
+                if self.current_class == ClassType::None {
+                    self.error(&this.keyword, "Can't use 'this' outside of a class.");
+                    return;
+                }
+                this.depth = self.resolve_local(&this.keyword);
+            }
+            Expr::Null => (),
+        }
+    }
+
+    fn resolve_local(&self, name: &Token) -> Option<usize> {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.contains_key(&name.lexeme) {
+                return Some(depth);
+            }
+        }
+        // Not found: assume it is global
+        return None;
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let already_declared = match self.scopes.last_mut() {
+            Some(scope) => scope.insert(name.lexeme.clone(), false).is_some(),
+            None => return,
+        };
+        if already_declared {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), true);
+        }
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.errors.push((token.clone(), String::from(message)));
+    }
+}
\ No newline at end of file
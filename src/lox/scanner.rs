@@ -1,24 +1,50 @@
 use super::token::*;
 use super::token_type::*;
 use std::collections::HashMap;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
 
 pub struct Scanner {
     source: Vec<u8>,
+    // Recorded in every span, see Span::source
+    source_id: u32,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: u32,
+    line_start: usize,
+    start_line: u32,
+    start_column: u32,
+    errors: Vec<(Span, String)>,
+    // Set when the input ended inside a string or comment
+    unterminated: bool,
+    // Braces opened inside each '${' still being scanned, innermost last
+    interpolations: Vec<usize>,
+    // Off by default; when set, '///' comments become DocComment tokens
+    doc_comments: bool,
+    // Doc comments waiting for the token that starts the next declaration
+    docs: Vec<Token>,
     keywords: HashMap<Vec<u8>, TokenType>,
 }
 
 impl Scanner {
-    pub fn new(source: Vec<u8>) -> Self {
+    pub fn new(source: Vec<u8>, source_id: u32) -> Self {
         Scanner {
             source: source,
+            source_id: source_id,
             tokens: vec![],
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            start_line: 1,
+            start_column: 1,
+            errors: vec![],
+            unterminated: false,
+            interpolations: vec![],
+            doc_comments: false,
+            docs: vec![],
             keywords: HashMap::from([
                 (b"and".to_vec(), TokenType::And),
                 (b"class".to_vec(), TokenType::Class),
@@ -27,6 +53,7 @@ impl Scanner {
                 (b"for".to_vec(), TokenType::For),
                 (b"fun".to_vec(), TokenType::Fun),
                 (b"if".to_vec(), TokenType::If),
+                (b"in".to_vec(), TokenType::In),
                 (b"nil".to_vec(), TokenType::Nil),
                 (b"or".to_vec(), TokenType::Or),
                 (b"print".to_vec(), TokenType::Print),
@@ -36,19 +63,40 @@ impl Scanner {
                 (b"true".to_vec(), TokenType::True),
                 (b"var".to_vec(), TokenType::Var),
                 (b"break".to_vec(), TokenType::Break),
-                (b"continue".to_vec(), TokenType::Break),
+                (b"continue".to_vec(), TokenType::Continue),
                 (b"while".to_vec(), TokenType::While),
                 ])
         }
     }
 
-    pub fn scan_tokens(&mut self) -> &Vec<Token> {
+    pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, Vec<(Span, String)>> {
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.column(self.start);
             self.scan_token();
         }
-        self.tokens.push(Token::new(TokenType::Eof, vec![], self.line, Literal::None));
-        &self.tokens
+        if !self.interpolations.is_empty() {
+            self.unterminated = true;
+            self.errors.push((self.end_span(), String::from("Unterminated string interpolation.")));
+        }
+        // Doc comments left over at the end have no declaration to document
+        self.take_docs(TokenType::Eof);
+        self.tokens.push(Token::new(TokenType::Eof, vec![], self.end_span(), Literal::None));
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+        Ok(&self.tokens)
+    }
+
+    // Keeps '///' comments, attaching them to the declaration that follows, so
+    // tooling can pick up the documentation of each one
+    pub fn keep_doc_comments(&mut self) {
+        self.doc_comments = true;
+    }
+
+    pub fn is_unterminated(&self) -> bool {
+        self.unterminated
     }
 
     fn is_at_end(&self) -> bool {
@@ -60,14 +108,87 @@ impl Scanner {
         match c {
             b'(' => self.add_token(TokenType::LeftParen),
             b')' => self.add_token(TokenType::RightParen),
-            b'{' => self.add_token(TokenType::LeftBrace),
-            b'}' => self.add_token(TokenType::RightBrace),
+            b'{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace);
+            },
+            b'[' => self.add_token(TokenType::LeftBracket),
+            b']' => self.add_token(TokenType::RightBracket),
+            b'}' => {
+                // The brace closing a '${' resumes the string around it
+                if self.interpolations.last() == Some(&0) {
+                    self.interpolations.pop();
+                    self.string(true);
+                    return;
+                }
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth -= 1;
+                }
+                self.add_token(TokenType::RightBrace);
+            },
             b',' => self.add_token(TokenType::Comma),
-            b'.' => self.add_token(TokenType::Dot),
-            b'-' => self.add_token(TokenType::Minus),
-            b'+' => self.add_token(TokenType::Plus),
+            b'.' => {
+                let token_type = if !self.compl('.') {
+                    TokenType::Dot
+                } else if self.compl('=') {
+                    TokenType::DotDotEqual
+                } else {
+                    TokenType::DotDot
+                };
+                self.add_token(token_type);
+            },
+            b'-' => {
+                let token_type = if self.compl('-') {
+                    TokenType::MinusMinus
+                } else if self.compl('=') {
+                    TokenType::MinusEqual
+                } else {
+                    TokenType::Minus
+                };
+                self.add_token(token_type);
+            },
+            b'+' => {
+                let token_type = if self.compl('+') {
+                    TokenType::PlusPlus
+                } else if self.compl('=') {
+                    TokenType::PlusEqual
+                } else {
+                    TokenType::Plus
+                };
+                self.add_token(token_type);
+            },
             b';' => self.add_token(TokenType::Semicolon),
-            b'*' => self.add_token(TokenType::Star),
+            b'*' => {
+                let token_type = if self.compl('*') {
+                    TokenType::StarStar
+                } else if self.compl('=') {
+                    TokenType::StarEqual
+                } else {
+                    TokenType::Star
+                };
+                self.add_token(token_type);
+            },
+            b'&' => self.add_token(TokenType::Ampersand),
+            b'|' => self.add_token(TokenType::Pipe),
+            b'^' => self.add_token(TokenType::Caret),
+            b'~' => {
+                let token_type = if self.compl('/') {
+                    TokenType::TildeSlash
+                } else {
+                    TokenType::Tilde
+                };
+                self.add_token(token_type);
+            },
+            b'%' => {
+                let token_type = if self.compl('=') {
+                    TokenType::PercentEqual
+                } else {
+                    TokenType::Percent
+                };
+                self.add_token(token_type);
+            },
             b'?' => self.add_token(TokenType::QuestionMark),
             b':' => self.add_token(TokenType::Colon),
             b'!' => {
@@ -89,6 +210,8 @@ impl Scanner {
             b'<' => {
                 let token_type = if self.compl('=') {
                     TokenType::LessEqual
+                } else if self.compl('<') {
+                    TokenType::LessLess
                 } else {
                     TokenType::Less
                 };
@@ -97,6 +220,8 @@ impl Scanner {
             b'>' => {
                 let token_type = if self.compl('=') {
                     TokenType::GreaterEqual
+                } else if self.compl('>') {
+                    TokenType::GreaterGreater
                 } else {
                     TokenType::Greater
                 };
@@ -105,31 +230,47 @@ impl Scanner {
             b'/' => {
                 if self.compl('/'){
                     while self.peek() != b'\n' && !self.is_at_end() { self.advance(); };
+                    self.doc_comment();
                 } else if self.compl('*'){
-                    while self.peek() != b'*' && self.peek_next() != b'/' && !self.is_at_end() { self.advance(); };
-                    self.current += 2;
+                    self.block_comment();
+                } else if self.compl('=') {
+                    self.add_token(TokenType::SlashEqual);
                 } else {
                     self.add_token(TokenType::Slash);
                 }
             },
             b' ' => {},
             b'\r' => {},
-            b'\n' => {self.line += 1;},
+            b'\t' => {},
+            b'\n' => self.new_line(),
             b'"' => {
-                self.string();
+                self.string(false);
             }
             _ => {
                 if self.is_digit(c) {
                     self.number();
                 } else if self.is_alpha(c) {
                     self.idenfitier();
+                } else if let Some(letter) = self.char_at(self.start).filter(|letter| letter.is_alphabetic()) {
+                    self.current = self.start + letter.len_utf8();
+                    self.idenfitier();
+                } else {
+                    self.unexpected_character();
                 }
             }
         }
     }
 
     fn idenfitier(&mut self){
-        while self.is_alpha_numeric(self.peek()) { self.advance(); };
+        loop {
+            if self.is_alpha_numeric(self.peek()) {
+                self.advance();
+            } else if let Some(letter) = self.char_at(self.current).filter(|letter| letter.is_alphanumeric()) {
+                self.current += letter.len_utf8();
+            } else {
+                break;
+            }
+        }
         let text = &self.source[self.start..self.current];
         let type_identifier = match self.keywords.get(text){
             Some(&t) => t,
@@ -141,34 +282,269 @@ impl Scanner {
     }
 
     fn number(&mut self) {
-        while self.is_digit(self.peek()) { self.advance();}
+        if self.previous() == b'0' {
+            let radix = match self.peek() {
+                b'x' | b'X' => Some((16, "hexadecimal")),
+                b'b' | b'B' => Some((2, "binary")),
+                b'o' | b'O' => Some((8, "octal")),
+                _ => None,
+            };
+            if let Some((radix, name)) = radix {
+                self.advance();
+                self.radix_number(radix, name);
+                return;
+            }
+        }
+
+        self.digits();
+        let mut is_float = false;
         if self.peek() == b'.' && self.is_digit(self.peek_next()) {
+            is_float = true;
             self.advance();
-            while self.is_digit(self.peek()) { self.advance(); }
-            
+            self.digits();
+        }
+        if self.peek() == b'e' || self.peek() == b'E' {
+            is_float = true;
+            self.advance();
+            if self.peek() == b'+' || self.peek() == b'-' {
+                self.advance();
+            }
+            if !self.is_digit(self.peek()) {
+                self.error(String::from("Expect digits in exponent."));
+                return;
+            }
+            self.digits();
+        }
+
+        let text = &self.source[self.start..self.current];
+        if !Self::separators_between_digits(text, |c| c.is_ascii_digit()) {
+            self.error(String::from("A digit separator must sit between two digits."));
+            return;
+        }
+        let text: String = text.iter().filter(|&&c| c != b'_').map(|&c| c as char).collect();
+        // Literals without a fractional part or exponent are integers
+        if is_float {
+            match text.parse::<f64>() {
+                Ok(value) => self.add_token_literal(TokenType::Number, Literal::Number(value)),
+                Err(_) => self.error(String::from("Invalid number literal.")),
+            }
+        } else {
+            match text.parse::<BigInt>() {
+                Ok(value) => self.add_token_literal(TokenType::Number, Literal::Int(value)),
+                Err(_) => self.error(String::from("Invalid number literal.")),
+            }
         }
-        let val: f32 = String::from_utf8(self.source[self.start..self.current].to_vec())
-        .unwrap()
-        .parse()
-        .unwrap();
-        self.add_token_literal(TokenType::Number, Literal::Number(val));
     }
 
-    fn string(&mut self) {
-        while self.peek() != b'"' && !self.is_at_end() {
-            if self.peek() == b'\n' {self.line+= 1;}
-            self.advance();
+    fn digits(&mut self) {
+        while self.is_digit(self.peek()) || self.peek() == b'_' { self.advance(); }
+    }
+
+    // Called after a '0x', '0b' or '0o' prefix. Letters and digits are all taken
+    // so that '0b102' is reported as a whole rather than split into two tokens.
+    fn radix_number(&mut self, radix: u32, name: &str) {
+        let digits_start = self.current;
+        while self.peek().is_ascii_alphanumeric() || self.peek() == b'_' { self.advance(); }
+        let digits = &self.source[digits_start..self.current];
+
+        if digits.iter().all(|&c| c == b'_') {
+            let prefix = String::from_utf8_lossy(&self.source[self.start..digits_start]).into_owned();
+            self.error(format!("Expect digits after '{}'.", prefix));
+            return;
+        }
+        if let Some(&invalid) = digits.iter().find(|&&c| c != b'_' && !(c as char).is_digit(radix)) {
+            self.error(format!("Invalid digit '{}' in {} literal.", invalid as char, name));
+            return;
+        }
+        // A separator may also follow the prefix, as in '0x_FF'
+        let mut text = vec![b'0'];
+        text.extend_from_slice(digits);
+        if !Self::separators_between_digits(&text, |c| (c as char).is_digit(radix)) {
+            self.error(String::from("A digit separator must sit between two digits."));
+            return;
+        }
+        let digits: Vec<u8> = digits.iter().copied().filter(|&c| c != b'_').collect();
+        match BigInt::parse_bytes(&digits, radix) {
+            Some(value) => self.add_token_literal(TokenType::Number, Literal::Int(value)),
+            None => self.error(format!("Invalid {} literal.", name)),
+        }
+    }
+
+    fn separators_between_digits(text: &[u8], is_digit: impl Fn(u8) -> bool) -> bool {
+        for (position, &c) in text.iter().enumerate() {
+            if c != b'_' {
+                continue;
+            }
+            let before = position > 0 && is_digit(text[position - 1]);
+            let after = position + 1 < text.len() && is_digit(text[position + 1]);
+            if !before || !after {
+                return false;
+            }
         }
+        return true;
+    }
+
+    // Scans from just after the opening '"', or after the '}' ending an
+    // interpolation, up to the closing '"' or the next '${'
+    fn string(&mut self, resumed: bool) {
+        let mut value: Vec<u8> = vec![];
+        loop {
+            if self.is_at_end() {
+                self.unterminated = true;
+                self.error(String::from("Unterminated string."));
+                return;
+            }
+            let c = self.advance();
+            match c {
+                b'"' => break,
+                b'\\' => self.escape(&mut value),
+                b'$' if self.peek() == b'{' => {
+                    self.advance();
+                    self.interpolations.push(0);
+                    let token_type = if resumed { TokenType::InterpolationMiddle } else { TokenType::Interpolation };
+                    self.add_string_token(token_type, value);
+                    return;
+                }
+                _ => {
+                    if c == b'\n' { self.new_line(); }
+                    value.push(c);
+                }
+            }
+        }
+        let token_type = if resumed { TokenType::InterpolationEnd } else { TokenType::String };
+        self.add_string_token(token_type, value);
+    }
 
+    fn escape(&mut self, value: &mut Vec<u8>) {
+        let escape_start = self.current - 1;
         if self.is_at_end() {
+            // Reported as an unterminated string
             return;
         }
+        match self.advance() {
+            b'n' => value.push(b'\n'),
+            b't' => value.push(b'\t'),
+            b'r' => value.push(b'\r'),
+            b'0' => value.push(b'\0'),
+            c @ (b'"' | b'\\' | b'$') => value.push(c),
+            b'u' => {
+                let opened = self.compl('{');
+                let digits_start = self.current;
+                while self.peek().is_ascii_hexdigit() { self.advance(); }
+                let digits = String::from_utf8_lossy(&self.source[digits_start..self.current]).into_owned();
+                let closed = opened && self.compl('}');
+                let character = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32);
+                match character {
+                    Some(character) if closed && digits.len() <= 6 => {
+                        value.extend_from_slice(character.encode_utf8(&mut [0; 4]).as_bytes());
+                    }
+                    _ => self.error_at(escape_start, String::from("Invalid unicode escape, expected '\\u{XXXX}'.")),
+                }
+            }
+            c => {
+                while self.peek() & 0b1100_0000 == 0b1000_0000 { self.advance(); }
+                let text = String::from_utf8_lossy(&self.source[escape_start..self.current]).into_owned();
+                self.error_at(escape_start, format!("Unknown escape sequence '{}'.", text.trim_end()));
+                if c == b'\n' { self.new_line(); }
+            }
+        }
+    }
+
+    fn add_string_token(&mut self, token_type: TokenType, value: Vec<u8>) {
+        match String::from_utf8(value) {
+            Ok(value) => self.add_token_literal(token_type, Literal::String(value)),
+            Err(_) => self.error(String::from("Invalid UTF-8 in string.")),
+        }
+    }
+
+    // Block comments nest, so each '/*' inside needs its own '*/'
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                self.unterminated = true;
+                self.error(String::from("Unterminated block comment."));
+                return;
+            }
+            if self.peek() == b'/' && self.peek_next() == b'*' {
+                self.current += 2;
+                depth += 1;
+            } else if self.peek() == b'*' && self.peek_next() == b'/' {
+                self.current += 2;
+                depth -= 1;
+            } else if self.advance() == b'\n' {
+                self.new_line();
+            }
+        }
+    }
+
+    // A line comment just scanned is a doc comment when it starts with exactly three
+    // slashes. Its literal is the text after them, less one leading space.
+    fn doc_comment(&mut self) {
+        let text = &self.source[self.start..self.current];
+        if !self.doc_comments || !text.starts_with(b"///") || text.starts_with(b"////") {
+            return;
+        }
+        let content = text[3..].strip_prefix(b" ").unwrap_or(&text[3..]);
+        let content = String::from_utf8_lossy(content).trim_end().to_string();
+        let span = Span::new(self.source_id, self.start, self.current - self.start, self.start_line, self.start_column);
+        self.docs.push(Token::new(TokenType::DocComment, text.to_vec(), span, Literal::String(content)));
+    }
+
+    // Only a token that can start a declaration takes the pending doc comments:
+    // 'class', 'fun', 'var', or the name of a method. Before anything else they
+    // are reported.
+    fn take_docs(&mut self, token_type: TokenType) -> Option<Rc<Vec<Token>>> {
+        if self.docs.is_empty() {
+            return None;
+        }
+        let docs = std::mem::take(&mut self.docs);
+        match token_type {
+            TokenType::Class | TokenType::Fun | TokenType::Var | TokenType::Identifier => return Some(Rc::new(docs)),
+            _ => {
+                self.errors.push((docs[0].span, String::from("Doc comment must come before a declaration.")));
+                return None;
+            }
+        }
+    }
+
+    fn unexpected_character(&mut self) {
+        // Swallow the rest of a multi-byte character so it is reported once
+        while self.peek() & 0b1100_0000 == 0b1000_0000 { self.advance(); }
+        let text = String::from_utf8_lossy(&self.source[self.start..self.current]).into_owned();
+        self.error(format!("Unexpected character '{}'.", text));
+    }
 
-        self.advance();
+    fn error(&mut self, message: String) {
+        let span = Span::new(self.source_id, self.start, self.current - self.start, self.start_line, self.start_column);
+        self.errors.push((span, message));
+    }
+
+    // Reports the source from 'offset' up to the current position, which must be on the current line
+    fn error_at(&mut self, offset: usize, message: String) {
+        let span = Span::new(self.source_id, offset, self.current - offset, self.line, self.column(offset));
+        self.errors.push((span, message));
+    }
 
-        let value = &self.source[self.start + 1..self.current - 1];
-        let value = core::str::from_utf8(value).unwrap().to_string();
-        self.add_token_literal(TokenType::String, Literal::String(value));
+    // Columns count characters rather than bytes
+    fn column(&self, offset: usize) -> u32 {
+        let line_start = self.line_start.min(offset);
+        let characters = self.source[line_start..offset]
+            .iter()
+            .filter(|&&c| c & 0b1100_0000 != 0b1000_0000)
+            .count();
+        characters as u32 + 1
+    }
+
+    // The character starting at 'offset', if the bytes there are valid UTF-8
+    fn char_at(&self, offset: usize) -> Option<char> {
+        let end = (offset + 4).min(self.source.len());
+        let bytes = &self.source[offset..end];
+        let valid = match std::str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(err) => std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap(),
+        };
+        valid.chars().next()
     }
 
     fn compl(&mut self, expected: char) -> bool {
@@ -184,7 +560,7 @@ impl Scanner {
     
     fn peek(&self) -> u8 {
         if self.is_at_end() { return b'\0'; }
-        self.source[self.current] as u8
+        self.source[self.current]
     }
 
     fn peek_next(&self) -> u8 {
@@ -193,7 +569,7 @@ impl Scanner {
     }
 
     fn is_alpha(&mut self,c: u8) -> bool {
-        return (c >= b'a' && c <= b'z') || (c >= b'A' && c <= b'Z') || c == b'_';
+        return c.is_ascii_alphabetic() || c == b'_';
     }
 
     fn is_alpha_numeric(&mut self, c: u8) -> bool {
@@ -201,12 +577,12 @@ impl Scanner {
     }
 
     fn is_digit(&self, c: u8) -> bool {
-        return c >= b'0' && c <= b'9';
+        return c.is_ascii_digit();
     }
 
     fn advance(&mut self) -> u8 {
         self.current += 1;
-        self.source[(self.current - 1) as usize]
+        self.source[self.current - 1]
     }
 
     fn add_token(&mut self, token_type: TokenType) {
@@ -215,6 +591,113 @@ impl Scanner {
     
     fn add_token_literal(&mut self, token_type: TokenType, literal: Literal){
         let text = &self.source[self.start..self.current];
-        self.tokens.push(Token::new(token_type, text.to_vec(), self.line, literal));
+        let span = Span::new(self.source_id, self.start, self.current - self.start, self.start_line, self.start_column);
+        let mut token = Token::new(token_type, text.to_vec(), span, literal);
+        token.docs = self.take_docs(token_type);
+        self.tokens.push(token);
+    }
+
+    fn previous(&self) -> u8 {
+        self.source[self.current - 1]
+    }
+
+    // Called after consuming a '\n' so columns restart on the next line
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+    }
+
+    // End of input is reported right after the last non-blank character
+    fn end_span(&self) -> Span {
+        let end = self.source
+            .iter()
+            .rposition(|c| !c.is_ascii_whitespace())
+            .map_or(0, |position| position + 1);
+        let line = 1 + self.source[..end].iter().filter(|&&c| c == b'\n').count() as u32;
+        let line_start = self.source[..end]
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |position| position + 1);
+        let column = self.source[line_start..end]
+            .iter()
+            .filter(|&&c| c & 0b1100_0000 != 0b1000_0000)
+            .count();
+        Span::new(self.source_id, end, 0, line, column as u32 + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(source: &str) -> Vec<(Span, String)> {
+        let mut scanner = Scanner::new(source.as_bytes().to_vec(), 0);
+        match scanner.scan_tokens() {
+            Ok(tokens) => panic!("{:?} scanned as {:?}", source, tokens),
+            Err(errors) => errors,
+        }
+    }
+
+    fn assert_error(source: &str, message: &str) {
+        let errors = errors(source);
+        assert_eq!(errors.len(), 1, "{:?}: {:?}", source, errors);
+        assert_eq!(errors[0].1, message, "{:?}", source);
+    }
+
+    fn literal(source: &str) -> Literal {
+        let mut scanner = Scanner::new(source.as_bytes().to_vec(), 0);
+        let tokens = scanner.scan_tokens().unwrap();
+        assert_eq!(tokens.len(), 2, "{:?} scanned as {:?}", source, tokens);
+        return tokens[0].literal.clone();
+    }
+
+    fn assert_int(source: &str, value: i64) {
+        match literal(source) {
+            Literal::Int(int) => assert_eq!(int, BigInt::from(value), "{:?}", source),
+            other => panic!("{:?} scanned as {:?}", source, other),
+        }
+    }
+
+    #[test]
+    fn radix_prefix_without_digits() {
+        assert_error("0x", "Expect digits after '0x'.");
+        assert_error("0b_", "Expect digits after '0b'.");
+    }
+
+    #[test]
+    fn invalid_digit_is_reported_for_the_whole_literal() {
+        let errors = errors("0b102");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1, "Invalid digit '2' in binary literal.");
+        assert_eq!((errors[0].0.offset, errors[0].0.length), (0, 5));
+        assert_error("0o8", "Invalid digit '8' in octal literal.");
+        assert_error("0xFG", "Invalid digit 'G' in hexadecimal literal.");
+    }
+
+    #[test]
+    fn exponent_needs_digits() {
+        assert_error("1e", "Expect digits in exponent.");
+        assert_error("1e+", "Expect digits in exponent.");
+    }
+
+    #[test]
+    fn separators_must_sit_between_digits() {
+        assert_error("1__0", "A digit separator must sit between two digits.");
+        assert_error("1_", "A digit separator must sit between two digits.");
+        assert_error("1_.5", "A digit separator must sit between two digits.");
+        assert_error("0xFF_", "A digit separator must sit between two digits.");
+    }
+
+    #[test]
+    fn valid_literals() {
+        assert_int("1_000", 1000);
+        assert_int("0xFF", 255);
+        assert_int("0x_FF", 255);
+        assert_int("0b1010", 10);
+        assert_int("0o17", 15);
+        match literal("1.5e3") {
+            Literal::Number(number) => assert_eq!(number, 1500.0),
+            other => panic!("1.5e3 scanned as {:?}", other),
+        }
     }
 }
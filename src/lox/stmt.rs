@@ -32,18 +32,78 @@ pub struct If {
 pub struct While {
     pub condition: Box<Expr>,
     pub body: Box<Statement>,
+    pub label: Option<Token>,
+}
+
+// 'depth' counts the enclosing loops to skip: 0 targets the innermost one
+#[derive(Clone, Debug)]
+pub struct Break {
+    pub label: Option<Token>,
+    pub depth: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Continue {
+    pub label: Option<Token>,
+    pub depth: usize,
+}
+
+// Kept as its own node rather than desugared into a while loop so that
+// 'continue' still runs the increment clause
+#[derive(Clone, Debug)]
+pub struct For {
+    pub initializer: Box<Statement>,
+    pub condition: Box<Expr>,
+    pub increment: Box<Expr>,
+    pub body: Box<Statement>,
+    pub label: Option<Token>,
+}
+
+// 'for (var name in iterable)'; 'keyword' is the 'in', where iteration errors are reported
+#[derive(Clone, Debug)]
+pub struct ForIn {
+    pub name: Token,
+    pub keyword: Token,
+    pub iterable: Box<Expr>,
+    pub body: Box<Statement>,
+    pub label: Option<Token>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Class {
+    pub name: Token,
+    pub superclass: Box<Expr>,
+    pub methods: Vec<Function>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Return {
+    pub keyword: Token,
+    pub value: Box<Expr>,
 }
 
 #[derive(Clone, Debug)]
 pub enum Statement {
     Block(Block),
+    Class(Class),
     Expression(Expression),
+    For(For),
+    ForIn(ForIn),
+    Function(Function),
     If(If),
     Print(Print),
+    Return(Return),
     Var(Var),
     While(While),
-    Break,
-    Continue,
+    Break(Break),
+    Continue(Continue),
     Null,
 }
 
@@ -68,10 +128,45 @@ impl Statement {
         })
     }
     
-    pub fn while_branch(condition: Expr, body: Statement) -> Self {
+    pub fn while_branch(condition: Expr, body: Statement, label: Option<Token>) -> Self {
         Statement::While(While {
             condition: Box::new(condition),
             body: Box::new(body),
+            label: label,
+        })
+    }
+
+    pub fn break_statement(label: Option<Token>, depth: usize) -> Self {
+        Statement::Break(Break {
+            label: label,
+            depth: depth,
+        })
+    }
+
+    pub fn continue_statement(label: Option<Token>, depth: usize) -> Self {
+        Statement::Continue(Continue {
+            label: label,
+            depth: depth,
+        })
+    }
+
+    pub fn for_loop(initializer: Statement, condition: Expr, increment: Expr, body: Statement, label: Option<Token>) -> Self {
+        Statement::For(For {
+            initializer: Box::new(initializer),
+            condition: Box::new(condition),
+            increment: Box::new(increment),
+            body: Box::new(body),
+            label: label,
+        })
+    }
+
+    pub fn for_in(name: Token, keyword: Token, iterable: Expr, body: Statement, label: Option<Token>) -> Self {
+        Statement::ForIn(ForIn {
+            name: name,
+            keyword: keyword,
+            iterable: Box::new(iterable),
+            body: Box::new(body),
+            label: label,
         })
     }
 
@@ -87,4 +182,19 @@ impl Statement {
             statements: statements,
         })
     }
+
+    pub fn class(name: Token, superclass: Expr, methods: Vec<Function>) -> Self {
+        Statement::Class(Class {
+            name: name,
+            superclass: Box::new(superclass),
+            methods: methods,
+        })
+    }
+
+    pub fn return_statement(keyword: Token, value: Expr) -> Self {
+        Statement::Return(Return {
+            keyword: keyword,
+            value: Box::new(value),
+        })
+    }
 }
\ No newline at end of file
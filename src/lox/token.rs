@@ -1,25 +1,71 @@
+use std::fmt;
+use std::rc::Rc;
+
+use num_bigint::BigInt;
+
 use super::token_type::*;
 
+// Location of a piece of source text, used to underline it in diagnostics.
+// Kept to 24 bytes, as every token and most nodes carry one.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Span {
+    pub offset: usize,
+    // Which of the texts run so far the offset points into, so a function
+    // defined in an earlier REPL line or loaded file still reports its own source
+    pub source: u32,
+    pub length: u32,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Span {
+    pub fn new(source: u32, offset: usize, length: usize, line: u32, column: u32) -> Self {
+        Span {
+            source: source,
+            offset: offset,
+            length: u32::try_from(length).unwrap_or(u32::MAX),
+            line: line,
+            column: column,
+        }
+    }
+
+    pub fn end(&self) -> usize {
+        self.offset + self.length as usize
+    }
+
+    // Smallest span covering both self and other
+    pub fn to(self, other: Span) -> Span {
+        let (first, last) = if self.offset <= other.offset { (self, other) } else { (other, self) };
+        let end = first.end().max(last.end());
+        Span::new(first.source, first.offset, end - first.offset, first.line, first.column)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: Vec<u8>,
     pub literal: Literal,
-    pub line: u32,
+    pub span: Span,
+    // Doc comments written just before the declaration this token starts, when
+    // the scanner keeps them.
+    // Shared rather than inline so tokens stay small.
+    pub docs: Option<Rc<Vec<Token>>>,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: Vec<u8>, line: u32, literal: Literal) -> Self {
+    pub fn new(token_type: TokenType, lexeme: Vec<u8>, span: Span, literal: Literal) -> Self {
         Token {
             token_type: token_type,
             lexeme: lexeme,
-            line: line,
+            span: span,
             literal: literal,
+            docs: None,
         }
     }
 
-    pub fn to_string(&self) -> String {
-        return format!("{:?} {:?}", self.token_type, self.lexeme);
+    pub fn docs(&self) -> &[Token] {
+        self.docs.as_deref().map_or(&[], |docs| docs.as_slice())
     }
 
     pub fn to_string_lexeme(&self) -> String {
@@ -27,9 +73,16 @@ impl Token {
     }
 }
 
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {} {:?}", self.token_type, String::from_utf8_lossy(&self.lexeme), self.literal)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Literal {
     String(String),
-    Number(f32),
+    Number(f64),
+    Int(BigInt),
     None,
 }
\ No newline at end of file
@@ -3,20 +3,33 @@ use core::mem::discriminant as tag;
 #[derive(Debug, Copy, Clone)]
 pub enum TokenType {
     // Single-character tokens.
-    LeftParen, RightParen, LeftBrace, RightBrace, 
+    LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
     Comma, Dot, Minus, Plus, Semicolon, Slash, Star,
-    QuestionMark, Colon,
+    QuestionMark, Colon, Ampersand, Pipe, Caret, Tilde,
     
     // One or two character tokens.
     Bang, BangEqual,
     Equal, EqualEqual,
     Greater, GreaterEqual,
     Less, LessEqual,
+    // Floor division is spelled '~/' (as in Dart), not the '//' the operator
+    // request asked for: in Lox '//' already starts a line comment, and
+    // 'a // b' would silently comment out the rest of the line. The spelling
+    // still needs the requester's agreement.
+    Percent, TildeSlash, StarStar,
+    PlusEqual, MinusEqual, StarEqual, SlashEqual, PercentEqual,
+    PlusPlus, MinusMinus,
+    LessLess, GreaterGreater,
+    DotDot, DotDotEqual,
      // Literals.
-    Identifier, String, Number,
+    // An interpolated string is split at each '${...}': '"a${' is an Interpolation,
+    // '}b${' an InterpolationMiddle and '}c"' the InterpolationEnd
+    Identifier, String, Interpolation, InterpolationMiddle, InterpolationEnd, Number,
     // Keywords.
     And, Class, Else, False, Fun, For, If, Nil, Or,
-    Print, Return, Super, This, True, Var, While, Break, Continue,
+    Print, Return, Super, This, True, Var, While, Break, Continue, In,
+    // Trivia: a '///' comment, only produced on request and attached to the declaration after it
+    DocComment,
     Eof,
 }
 